@@ -1,10 +1,850 @@
-use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::traits::{DeviceTrait, StreamTrait};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 use std::sync::{Arc, Mutex};
-use std::path::PathBuf;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::path::{Path, PathBuf};
+use tokio::sync::mpsc;
+use crate::audio_devices::{
+    find_loopback_device, get_input_device_by_name, resolve_input_device, AudioError,
+};
+use crate::flac::FlacWriter;
+
+/// Quem está falando, de acordo com o stream que capturou o áudio
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Speaker {
+    /// Microfone local (o vendedor)
+    Seller,
+    /// Áudio do sistema / loopback (o cliente do outro lado da chamada)
+    Customer,
+}
+
+impl Speaker {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Speaker::Seller => "seller",
+            Speaker::Customer => "customer",
+        }
+    }
+}
+
+/// Dispositivos efetivamente abertos por uma captura
+#[derive(Debug, Clone, Serialize)]
+pub struct CaptureDevices {
+    pub microphone: String,
+    pub loopback: Option<String>,
+}
+
+/// Quantos parciais e eventos de nível podem aguardar o consumidor antes de a captura
+/// começar a descartar
+const CAPTURE_CHANNEL_CAPACITY: usize = 32;
+
+/// Quantos chunks e limites de fala podem aguardar o consumidor. Com a fila cheia o
+/// chunk não é entregue, mas continua em `pending_dir` para uma nova tentativa.
+const EVENT_CHANNEL_CAPACITY: usize = 64;
+
+/// Duração da janela usada pelo detector de voz
+pub(crate) const VAD_FRAME_MS: u64 = 20;
+
+/// Janela de medição de nível de entrada
+const LEVEL_WINDOW_MS: u64 = 50;
+
+/// Intervalo mínimo entre dois avisos de clipping do mesmo stream
+const CLIPPING_COOLDOWN_MS: u64 = 1000;
+
+/// Parâmetros do medidor de nível exibido na interface
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LevelOptions {
+    /// Intervalo mínimo entre eventos `audio-level` de um stream
+    pub interval_ms: u64,
+    /// Pico (0.0-1.0) a partir do qual o sinal é considerado clipado
+    pub clipping_threshold: f32,
+    /// RMS abaixo do qual a entrada é considerada muda
+    pub silence_threshold: f32,
+    /// Segundos de entrada muda até o aviso `input-silent` (0 desliga)
+    pub silence_warning_secs: u64,
+}
+
+impl Default for LevelOptions {
+    fn default() -> Self {
+        LevelOptions {
+            interval_ms: 100,
+            clipping_threshold: 0.99,
+            silence_threshold: 0.001,
+            silence_warning_secs: 10,
+        }
+    }
+}
+
+/// Parâmetros do detector de voz que decide onde cortar os chunks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VadOptions {
+    /// RMS (0.0-1.0) acima do qual uma janela conta como fala
+    pub energy_threshold: f32,
+    /// Silêncio contínuo que encerra o chunk atual
+    pub silence_ms: u64,
+    /// Chunks nunca fecham antes disso (Whisper vai mal com trechos muito curtos)
+    pub min_chunk_ms: u64,
+    /// Fala contínua é cortada ao atingir esse tamanho
+    pub max_chunk_ms: u64,
+    /// Áudio mantido antes do início e depois do fim da fala
+    pub padding_ms: u64,
+    /// Chunks com menos fala que isso são descartados como ruído
+    pub min_speech_ms: u64,
+    /// Quando a fala é cortada por `max_chunk_ms`, o próximo chunk repete esse final
+    /// do anterior, para nenhuma palavra ficar partida ao meio
+    pub overlap_ms: u64,
+    /// Com fala em andamento, a cada quanto tempo o chunk aberto é enviado como parcial
+    /// (0 desliga os parciais)
+    pub partial_interval_ms: u64,
+}
+
+impl Default for VadOptions {
+    fn default() -> Self {
+        VadOptions {
+            energy_threshold: 0.01,
+            silence_ms: 600,
+            min_chunk_ms: 1000,
+            max_chunk_ms: 10_000,
+            padding_ms: 200,
+            min_speech_ms: 200,
+            overlap_ms: 300,
+            partial_interval_ms: 1500,
+        }
+    }
+}
+
+/// Taxa de amostragem que o Whisper usa internamente
+pub const WHISPER_SAMPLE_RATE: u32 = 16_000;
+
+/// Opções de captura configuráveis pelo usuário
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CaptureOptions {
+    /// Grava também cada chunk como `chunk_<speaker>_<ms>.wav` no diretório base
+    pub save_chunks: bool,
+    /// Taxa (mono) para a qual o áudio é convertido antes de virar chunk
+    pub target_sample_rate: u32,
+    pub vad: VadOptions,
+    pub level: LevelOptions,
+}
+
+impl Default for CaptureOptions {
+    fn default() -> Self {
+        CaptureOptions {
+            save_chunks: false,
+            target_sample_rate: WHISPER_SAMPLE_RATE,
+            vad: VadOptions::default(),
+            level: LevelOptions::default(),
+        }
+    }
+}
+
+/// Início ou fim de fala detectado em um stream
+#[derive(Debug, Clone, Serialize)]
+pub struct SpeechBoundary {
+    pub speaker: Speaker,
+    /// `true` quando a fala começou, `false` quando terminou
+    pub speaking: bool,
+    /// Momento em ms, relativo ao início da sessão
+    pub at_ms: u64,
+}
+
+/// O que a captura entrega ao consumidor
+#[derive(Debug, Clone)]
+pub enum CaptureEvent {
+    Chunk(AudioChunk),
+    Speech(SpeechBoundary),
+}
+
+/// Nível de entrada de um stream em um intervalo
+#[derive(Debug, Clone, Serialize)]
+pub struct AudioLevel {
+    pub speaker: Speaker,
+    /// RMS e pico (0.0-1.0) do sinal original, antes da conversão
+    pub rms: f32,
+    pub peak: f32,
+    pub at_ms: u64,
+}
+
+/// Entrada muda há mais tempo que o configurado (mic mutado, cabo solto...)
+#[derive(Debug, Clone, Serialize)]
+pub struct InputSilence {
+    pub speaker: Speaker,
+    pub seconds: u64,
+    pub at_ms: u64,
+}
+
+/// Telemetria de nível, entregue em um canal separado para nunca disputar
+/// espaço com os chunks que aguardam transcrição
+#[derive(Debug, Clone)]
+pub enum MeterEvent {
+    Level(AudioLevel),
+    Clipping(AudioLevel),
+    Silent(InputSilence),
+}
+
+/// Uma captura em andamento: dispositivos abertos e os canais de saída
+pub struct Capture {
+    pub devices: CaptureDevices,
+    /// Limitado: com o consumidor muito atrasado (ex.: backend fora do ar), os chunks
+    /// que não cabem ficam só nos pendentes, em vez de acumular na memória
+    pub events: mpsc::Receiver<CaptureEvent>,
+    /// Cópias do chunk ainda aberto, do início até agora; o `Chunk` de mesmo
+    /// `utterance_id` vem depois
+    pub partials: mpsc::Receiver<AudioChunk>,
+    pub meters: mpsc::Receiver<MeterEvent>,
+}
+
+/// Para onde o segmentador de um stream manda o que produz
+#[derive(Clone)]
+struct ChunkOutputs {
+    /// Chunks e limites de fala, para a thread de disco do stream: enviar nunca bloqueia
+    /// o callback do cpal
+    events: std::sync::mpsc::Sender<CaptureEvent>,
+    /// Parciais são descartados quando o consumidor está atrasado
+    partials: mpsc::Sender<AudioChunk>,
+}
+
+/// Onde a thread de disco de um stream grava cada chunk antes de entregá-lo
+#[derive(Clone)]
+struct ChunkFiles {
+    /// Diretório dos arquivos de chunk, se `save_chunks`
+    save_dir: Option<PathBuf>,
+    /// Chunks aguardando transcrição, gravados assim que fecham
+    pending_dir: Option<PathBuf>,
+}
+
+impl ChunkFiles {
+    /// Grava os WAVs do chunk; com `save_dir`, o chunk sai apontando para o arquivo
+    fn store(&self, mut chunk: AudioChunk) -> AudioChunk {
+        if let Some(ref dir) = self.save_dir {
+            let path = dir.join(chunk.file_name());
+            match chunk.write_wav(&path) {
+                Ok(()) => chunk.path = Some(path),
+                Err(e) => eprintln!("⚠️ {}", e),
+            }
+        }
+        // Em disco antes de entrar na fila: se a transcrição nunca acontecer, dá para repetir
+        if let Some(ref dir) = self.pending_dir {
+            if let Err(e) = chunk.write_wav(&dir.join(chunk.file_name())) {
+                eprintln!("⚠️ {}", e);
+            }
+        }
+        chunk
+    }
+}
+
+/// Para onde cada stream de uma captura entrega o que grava
+#[derive(Clone)]
+struct StreamOutputs {
+    events: mpsc::Sender<CaptureEvent>,
+    partials: mpsc::Sender<AudioChunk>,
+    files: ChunkFiles,
+    meters: mpsc::Sender<MeterEvent>,
+    /// Diretório da gravação contínua da sessão
+    archive_dir: Option<PathBuf>,
+}
+
+/// Trecho de áudio finalizado pela captura, entregue direto à transcrição
+#[derive(Debug, Clone)]
+pub struct AudioChunk {
+    pub speaker: Speaker,
+    /// Samples intercalados (`channels` por frame), em [-1.0, 1.0]
+    pub samples: Vec<f32>,
+    pub sample_rate: u32,
+    pub channels: u16,
+    /// Início e fim em ms, relativos ao início da sessão
+    pub start_ms: u64,
+    pub end_ms: u64,
+    /// Caminho do WAV, quando `save_chunks` está ligado
+    pub path: Option<PathBuf>,
+}
+
+impl AudioChunk {
+    /// Identifica o trecho de fala: os parciais e o chunk final dele compartilham o id
+    pub fn utterance_id(&self) -> String {
+        format!("{}_{}", self.speaker.as_str(), self.start_ms)
+    }
+
+    /// Nome do WAV do chunk (`chunk_<speaker>_<ms>.wav`)
+    pub fn file_name(&self) -> String {
+        format!("chunk_{}_{}.wav", self.speaker.as_str(), self.start_ms)
+    }
+
+    fn wav_spec(&self) -> hound::WavSpec {
+        hound::WavSpec {
+            channels: self.channels,
+            sample_rate: self.sample_rate,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        }
+    }
+
+    fn write_samples<W: std::io::Write + std::io::Seek>(
+        &self,
+        mut writer: hound::WavWriter<W>,
+    ) -> Result<(), String> {
+        for &sample in &self.samples {
+            let amplitude = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+            writer
+                .write_sample(amplitude)
+                .map_err(|e| format!("Erro ao escrever sample: {}", e))?;
+        }
+        writer
+            .finalize()
+            .map_err(|e| format!("Erro ao finalizar WAV: {}", e))
+    }
+
+    /// Grava o chunk como WAV 16 bits no caminho indicado
+    pub fn write_wav(&self, path: &Path) -> Result<(), String> {
+        let writer = hound::WavWriter::create(path, self.wav_spec())
+            .map_err(|e| format!("Erro ao criar {:?}: {}", path, e))?;
+        self.write_samples(writer)
+    }
+
+    /// Lê de volta um chunk gravado por `write_wav`
+    pub fn read_wav(path: &Path, speaker: Speaker, start_ms: u64) -> Result<AudioChunk, String> {
+        let reader = hound::WavReader::open(path)
+            .map_err(|e| format!("Erro ao abrir {:?}: {}", path, e))?;
+        let spec = reader.spec();
+        let samples = reader
+            .into_samples::<i16>()
+            .map(|sample| sample.map(|s| s as f32 / i16::MAX as f32))
+            .collect::<Result<Vec<f32>, _>>()
+            .map_err(|e| format!("Erro ao ler {:?}: {}", path, e))?;
+
+        let frames = samples.len() as u64 / spec.channels.max(1) as u64;
+        Ok(AudioChunk {
+            speaker,
+            samples,
+            sample_rate: spec.sample_rate,
+            channels: spec.channels,
+            start_ms,
+            end_ms: start_ms + frames * 1000 / spec.sample_rate.max(1) as u64,
+            path: Some(path.to_path_buf()),
+        })
+    }
+
+    /// WAV em memória, para enviar direto a uma API sem passar pelo disco
+    pub fn to_wav_bytes(&self) -> Result<Vec<u8>, String> {
+        let mut cursor = std::io::Cursor::new(Vec::new());
+        let writer = hound::WavWriter::new(&mut cursor, self.wav_spec())
+            .map_err(|e| format!("Erro ao criar WAV: {}", e))?;
+        self.write_samples(writer)?;
+        Ok(cursor.into_inner())
+    }
+}
+
+/// Converte samples intercalados em mono pela média dos canais
+pub fn downmix_to_mono(samples: &[f32], channels: u16) -> Vec<f32> {
+    if channels <= 1 {
+        return samples.to_vec();
+    }
+
+    samples
+        .chunks_exact(channels as usize)
+        .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+        .collect()
+}
+
+/// Reamostragem linear incremental de um sinal mono. Guarda o último sample de cada
+/// bloco para que a interpolação continue sem emendas entre callbacks. Ao reduzir a taxa,
+/// uma média móvel do tamanho da razão atenua o que ficaria acima da nova Nyquist.
+pub struct Resampler {
+    /// Quantos samples de entrada avançam por sample de saída
+    step: f64,
+    /// Posição do próximo sample de saída, relativa a `previous`
+    position: f64,
+    previous: Option<f32>,
+    /// Janela da média móvel (1 = sem filtro)
+    filter_width: usize,
+    filter_history: VecDeque<f32>,
+    filter_sum: f32,
+}
+
+impl Resampler {
+    pub fn new(from_rate: u32, to_rate: u32) -> Self {
+        let step = from_rate as f64 / to_rate as f64;
+        let filter_width = if step > 1.0 { step.round() as usize } else { 1 };
+
+        Resampler {
+            step,
+            position: 0.0,
+            previous: None,
+            filter_width,
+            filter_history: VecDeque::with_capacity(filter_width),
+            filter_sum: 0.0,
+        }
+    }
+
+    fn low_pass(&mut self, sample: f32) -> f32 {
+        if self.filter_width <= 1 {
+            return sample;
+        }
+
+        self.filter_history.push_back(sample);
+        self.filter_sum += sample;
+        if self.filter_history.len() > self.filter_width {
+            self.filter_sum -= self.filter_history.pop_front().unwrap_or(0.0);
+        }
+        self.filter_sum / self.filter_history.len() as f32
+    }
+
+    pub fn process(&mut self, input: &[f32]) -> Vec<f32> {
+        if self.step == 1.0 {
+            return input.to_vec();
+        }
+
+        let filtered: Vec<f32> = input.iter().map(|&s| self.low_pass(s)).collect();
+        let mut input = filtered.as_slice();
+
+        let previous = match self.previous {
+            Some(sample) => sample,
+            None => match input.split_first() {
+                Some((&first, rest)) => {
+                    input = rest;
+                    first
+                }
+                None => return Vec::new(),
+            },
+        };
+
+        // x[0] = previous, x[1..=n] = input
+        let n = input.len();
+        let sample_at = |i: usize| if i == 0 { previous } else { input[i - 1] };
+        let mut output = Vec::with_capacity((n as f64 / self.step) as usize + 1);
+
+        while self.position < n as f64 {
+            let index = self.position.floor() as usize;
+            let frac = (self.position - index as f64) as f32;
+            let a = sample_at(index);
+            let b = sample_at(index + 1);
+            output.push(a + (b - a) * frac);
+            self.position += self.step;
+        }
+
+        self.position -= n as f64;
+        self.previous = Some(sample_at(n));
+        output
+    }
+}
+
+/// Etapa de conversão da captura: downmix para mono e reamostragem para a taxa alvo
+struct FormatConverter {
+    channels: u16,
+    resampler: Resampler,
+}
+
+impl FormatConverter {
+    fn new(channels: u16, from_rate: u32, to_rate: u32) -> Self {
+        FormatConverter {
+            channels,
+            resampler: Resampler::new(from_rate, to_rate),
+        }
+    }
+
+    fn process(&mut self, data: &[f32]) -> Vec<f32> {
+        let mono = downmix_to_mono(data, self.channels);
+        self.resampler.process(&mono)
+    }
+}
+
+/// Mede RMS/pico do sinal bruto em janelas de `LEVEL_WINDOW_MS` e avisa sobre clipping
+/// e silêncio prolongado. Os eventos de nível são agregados por `interval_ms`.
+struct LevelMeter {
+    speaker: Speaker,
+    sample_rate: u32,
+    channels: u16,
+    options: LevelOptions,
+    /// Samples por janela (todos os canais)
+    window_len: usize,
+    window_sum_sq: f32,
+    window_peak: f32,
+    window_count: usize,
+    /// Máximos acumulados desde o último `audio-level`
+    interval_rms: f32,
+    interval_peak: f32,
+    interval_ms: u64,
+    frames_seen: u64,
+    silent_ms: u64,
+    silence_reported: bool,
+    last_clipping_ms: Option<u64>,
+    sender: mpsc::Sender<MeterEvent>,
+}
+
+impl LevelMeter {
+    fn new(
+        speaker: Speaker,
+        sample_rate: u32,
+        channels: u16,
+        options: LevelOptions,
+        sender: mpsc::Sender<MeterEvent>,
+    ) -> Self {
+        let window_len = (sample_rate as u64 * LEVEL_WINDOW_MS / 1000) as usize * channels as usize;
+
+        LevelMeter {
+            speaker,
+            sample_rate,
+            channels,
+            options,
+            window_len: window_len.max(1),
+            window_sum_sq: 0.0,
+            window_peak: 0.0,
+            window_count: 0,
+            interval_rms: 0.0,
+            interval_peak: 0.0,
+            interval_ms: 0,
+            frames_seen: 0,
+            silent_ms: 0,
+            silence_reported: false,
+            last_clipping_ms: None,
+            sender,
+        }
+    }
+
+    fn push(&mut self, data: &[f32]) {
+        for &sample in data {
+            self.window_sum_sq += sample * sample;
+            self.window_peak = self.window_peak.max(sample.abs());
+            self.window_count += 1;
+
+            if self.window_count >= self.window_len {
+                self.close_window();
+            }
+        }
+    }
+
+    fn close_window(&mut self) {
+        let rms = (self.window_sum_sq / self.window_count as f32).sqrt();
+        let peak = self.window_peak;
+        self.frames_seen += (self.window_count / self.channels as usize) as u64;
+        self.window_sum_sq = 0.0;
+        self.window_peak = 0.0;
+        self.window_count = 0;
+
+        let at_ms = self.frames_seen * 1000 / self.sample_rate as u64;
+        let level = AudioLevel {
+            speaker: self.speaker,
+            rms,
+            peak,
+            at_ms,
+        };
+
+        self.interval_rms = self.interval_rms.max(rms);
+        self.interval_peak = self.interval_peak.max(peak);
+        self.interval_ms += LEVEL_WINDOW_MS;
+        if self.interval_ms >= self.options.interval_ms {
+            self.send(MeterEvent::Level(AudioLevel {
+                rms: self.interval_rms,
+                peak: self.interval_peak,
+                ..level.clone()
+            }));
+            self.interval_rms = 0.0;
+            self.interval_peak = 0.0;
+            self.interval_ms = 0;
+        }
+
+        let clipping_due = self
+            .last_clipping_ms
+            .is_none_or(|last| at_ms.saturating_sub(last) >= CLIPPING_COOLDOWN_MS);
+        if peak >= self.options.clipping_threshold && clipping_due {
+            self.last_clipping_ms = Some(at_ms);
+            self.send(MeterEvent::Clipping(level));
+        }
+
+        if rms < self.options.silence_threshold {
+            self.silent_ms += LEVEL_WINDOW_MS;
+            let warning_ms = self.options.silence_warning_secs * 1000;
+            if warning_ms > 0 && !self.silence_reported && self.silent_ms >= warning_ms {
+                self.silence_reported = true;
+                self.send(MeterEvent::Silent(InputSilence {
+                    speaker: self.speaker,
+                    seconds: self.silent_ms / 1000,
+                    at_ms,
+                }));
+            }
+        } else {
+            self.silent_ms = 0;
+            self.silence_reported = false;
+        }
+    }
+
+    fn send(&self, event: MeterEvent) {
+        // Telemetria: se a interface estiver atrasada, descartar é o certo
+        let _ = self.sender.try_send(event);
+    }
+}
+
+/// Segmenta um stream por energia: abre um chunk quando a fala começa e fecha na
+/// próxima pausa, respeitando os limites de tamanho de `VadOptions`
+struct Segmenter {
+    speaker: Speaker,
+    sample_rate: u32,
+    channels: u16,
+    options: VadOptions,
+    /// Samples por janela do VAD (todos os canais)
+    frame_len: usize,
+    /// Samples recebidos que ainda não completam uma janela
+    pending: Vec<f32>,
+    /// Últimas janelas de silêncio, usadas como padding antes da fala
+    pre_roll: VecDeque<f32>,
+    /// Chunk em construção (vazio fora de fala)
+    buffer: Vec<f32>,
+    in_speech: bool,
+    chunk_start_frame: u64,
+    speech_frames: u64,
+    trailing_silence_frames: u64,
+    /// Janelas desde o último parcial do chunk atual
+    partial_frames: u64,
+    /// Janelas já processadas, para calcular o tempo relativo à sessão
+    frames_seen: u64,
+    outputs: ChunkOutputs,
+}
+
+impl Segmenter {
+    fn new(
+        speaker: Speaker,
+        sample_rate: u32,
+        channels: u16,
+        options: VadOptions,
+        outputs: ChunkOutputs,
+    ) -> Self {
+        let frame_len = (sample_rate as u64 * VAD_FRAME_MS / 1000) as usize * channels as usize;
+
+        Segmenter {
+            speaker,
+            sample_rate,
+            channels,
+            options,
+            frame_len: frame_len.max(channels as usize),
+            pending: Vec::new(),
+            pre_roll: VecDeque::new(),
+            buffer: Vec::new(),
+            in_speech: false,
+            chunk_start_frame: 0,
+            speech_frames: 0,
+            trailing_silence_frames: 0,
+            partial_frames: 0,
+            frames_seen: 0,
+            outputs,
+        }
+    }
+
+    fn push(&mut self, data: &[f32]) {
+        self.pending.extend_from_slice(data);
+
+        while self.pending.len() >= self.frame_len {
+            let rest = self.pending.split_off(self.frame_len);
+            let frame = std::mem::replace(&mut self.pending, rest);
+            self.process_frame(&frame);
+        }
+    }
+
+    /// Entrega a fala em andamento (fim da gravação)
+    fn flush(&mut self) {
+        if self.in_speech {
+            self.close_chunk(true);
+        }
+    }
+
+    fn frames_to_ms(&self, frames: u64) -> u64 {
+        frames * VAD_FRAME_MS
+    }
+
+    fn ms_to_frames(&self, ms: u64) -> u64 {
+        ms / VAD_FRAME_MS
+    }
+
+    fn process_frame(&mut self, frame: &[f32]) {
+        let energy = (frame.iter().map(|s| s * s).sum::<f32>() / frame.len() as f32).sqrt();
+        let is_speech = energy >= self.options.energy_threshold;
+
+        if !self.in_speech {
+            if is_speech {
+                let pre_roll_frames = (self.pre_roll.len() / self.frame_len) as u64;
+                self.in_speech = true;
+                self.chunk_start_frame = self.frames_seen - pre_roll_frames;
+                self.speech_frames = 0;
+                self.trailing_silence_frames = 0;
+                self.buffer.extend(self.pre_roll.drain(..));
+                self.send_boundary(true, self.frames_to_ms(self.frames_seen));
+            } else {
+                self.pre_roll.extend(frame.iter().copied());
+                let max_pre_roll = self.ms_to_frames(self.options.padding_ms) as usize * self.frame_len;
+                while self.pre_roll.len() > max_pre_roll {
+                    self.pre_roll.pop_front();
+                }
+                self.frames_seen += 1;
+                return;
+            }
+        }
+
+        self.buffer.extend_from_slice(frame);
+        self.frames_seen += 1;
+        self.partial_frames += 1;
+        if is_speech {
+            self.speech_frames += 1;
+            self.trailing_silence_frames = 0;
+        } else {
+            self.trailing_silence_frames += 1;
+        }
+
+        let chunk_ms = self.frames_to_ms(self.frames_seen - self.chunk_start_frame);
+        let silence_ms = self.frames_to_ms(self.trailing_silence_frames);
+
+        if silence_ms >= self.options.silence_ms && chunk_ms >= self.options.min_chunk_ms {
+            self.close_chunk(true);
+        } else if chunk_ms >= self.options.max_chunk_ms {
+            // Fala longa sem pausa: corta aqui e continua no próximo chunk
+            self.close_chunk(false);
+        } else if self.options.partial_interval_ms > 0
+            && self.frames_to_ms(self.partial_frames) >= self.options.partial_interval_ms
+            && self.frames_to_ms(self.speech_frames) >= self.options.min_speech_ms
+        {
+            self.emit_partial();
+        }
+    }
+
+    /// Fecha o chunk atual. Com `speech_ended`, apara o silêncio final além do padding
+    /// e sai do estado de fala; senão, o próximo chunk já começa com os últimos
+    /// `overlap_ms` deste.
+    fn close_chunk(&mut self, speech_ended: bool) {
+        let mut samples = std::mem::take(&mut self.buffer);
+        let mut end_frame = self.frames_seen;
+
+        if speech_ended {
+            let padding_frames = self.ms_to_frames(self.options.padding_ms);
+            let excess = self.trailing_silence_frames.saturating_sub(padding_frames);
+            let excess_samples = (excess as usize * self.frame_len).min(samples.len());
+            samples.truncate(samples.len() - excess_samples);
+            end_frame -= excess;
+
+            self.in_speech = false;
+            let speech_end = self.frames_seen - self.trailing_silence_frames;
+            self.send_boundary(false, self.frames_to_ms(speech_end));
+        }
+
+        let start_frame = self.chunk_start_frame;
+        let speech_ms = self.frames_to_ms(self.speech_frames);
+        self.chunk_start_frame = self.frames_seen;
+        self.speech_frames = 0;
+        self.trailing_silence_frames = 0;
+        self.partial_frames = 0;
+
+        if !speech_ended {
+            // Nunca metade do chunk ou mais, senão o próximo já nasceria no limite
+            let overlap_frames = self
+                .ms_to_frames(self.options.overlap_ms)
+                .min(self.ms_to_frames(self.options.max_chunk_ms) / 2)
+                .min(self.frames_seen - start_frame);
+            let overlap_samples = (overlap_frames as usize * self.frame_len).min(samples.len());
+            self.buffer.extend_from_slice(&samples[samples.len() - overlap_samples..]);
+            self.chunk_start_frame -= overlap_frames;
+        }
+
+        // Só ruído/silêncio: não vale uma chamada de transcrição
+        if speech_ms < self.options.min_speech_ms {
+            println!("🔇 Chunk sem fala descartado ({})", self.speaker.as_str());
+            return;
+        }
+
+        self.emit_chunk(samples, self.frames_to_ms(start_frame), self.frames_to_ms(end_frame));
+    }
+
+    fn send_boundary(&self, speaking: bool, at_ms: u64) {
+        let boundary = SpeechBoundary {
+            speaker: self.speaker,
+            speaking,
+            at_ms,
+        };
+        let _ = self.outputs.events.send(CaptureEvent::Speech(boundary));
+    }
+
+    /// Envia o chunk aberto até aqui. Fila cheia: o parcial é só descartado, o próximo
+    /// já traz mais áudio.
+    fn emit_partial(&mut self) {
+        self.partial_frames = 0;
+        let partial = AudioChunk {
+            speaker: self.speaker,
+            samples: self.buffer.clone(),
+            sample_rate: self.sample_rate,
+            channels: self.channels,
+            start_ms: self.frames_to_ms(self.chunk_start_frame),
+            end_ms: self.frames_to_ms(self.frames_seen),
+            path: None,
+        };
+        let _ = self.outputs.partials.try_send(partial);
+    }
+
+    /// Entrega o chunk fechado à thread de disco, que grava os arquivos dele
+    fn emit_chunk(&self, samples: Vec<f32>, start_ms: u64, end_ms: u64) {
+        let chunk = AudioChunk {
+            speaker: self.speaker,
+            samples,
+            sample_rate: self.sample_rate,
+            channels: self.channels,
+            start_ms,
+            end_ms,
+            path: None,
+        };
+        let _ = self.outputs.events.send(CaptureEvent::Chunk(chunk));
+    }
+}
+
+/// Thread da gravação contínua de um stream: codifica o FLAC fora do callback do cpal.
+/// Fecha o arquivo e termina quando o callback é descartado.
+fn spawn_archive_writer(mut writer: FlacWriter, blocks: std::sync::mpsc::Receiver<Vec<f32>>) -> std::thread::JoinHandle<()> {
+    std::thread::spawn(move || {
+        for block in blocks {
+            if let Err(e) = writer.write_samples(&block) {
+                eprintln!("⚠️ Erro ao gravar sessão: {}", e);
+                return;
+            }
+        }
+        if let Err(e) = writer.finish() {
+            eprintln!("⚠️ Erro ao finalizar gravação da sessão: {}", e);
+        }
+    })
+}
+
+/// Thread de disco de um stream: grava os arquivos de cada chunk e só então o entrega ao
+/// consumidor, na ordem em que o segmentador produziu. Termina quando o segmentador é
+/// descartado.
+fn spawn_disk_writer(
+    work: std::sync::mpsc::Receiver<CaptureEvent>,
+    events: mpsc::Sender<CaptureEvent>,
+    files: ChunkFiles,
+) -> std::thread::JoinHandle<()> {
+    std::thread::spawn(move || {
+        for event in work {
+            let event = match event {
+                CaptureEvent::Chunk(chunk) => {
+                    let chunk = files.store(chunk);
+                    println!("✅ Chunk finalizado ({}, {}-{} ms)", chunk.speaker.as_str(), chunk.start_ms, chunk.end_ms);
+                    CaptureEvent::Chunk(chunk)
+                }
+                speech => speech,
+            };
+            // `Closed` só se ninguém está consumindo (ex.: gravação simples sem transcrição)
+            if let Err(mpsc::error::TrySendError::Full(CaptureEvent::Chunk(chunk))) = events.try_send(event) {
+                eprintln!(
+                    "⚠️ Fila de transcrição cheia: chunk {} ms ({}) fica nos pendentes",
+                    chunk.start_ms,
+                    chunk.speaker.as_str()
+                );
+            }
+        }
+    })
+}
 
 pub struct AudioRecorder {
     is_recording: Arc<Mutex<bool>>,
+    /// Pausado: os streams continuam abertos, mas o áudio é descartado
+    is_paused: Arc<Mutex<bool>>,
+    last_error: Arc<Mutex<Option<AudioError>>>,
     base_dir: PathBuf,
 }
 
@@ -18,6 +858,8 @@ impl AudioRecorder {
         
         AudioRecorder {
             is_recording: Arc::new(Mutex::new(false)),
+            is_paused: Arc::new(Mutex::new(false)),
+            last_error: Arc::new(Mutex::new(None)),
             base_dir,
         }
     }
@@ -26,110 +868,256 @@ impl AudioRecorder {
         self.base_dir.to_string_lossy().to_string()
     }
 
-    pub fn start_recording(&self) -> Result<(), String> {
-        let host = cpal::default_host();
-        
-        let device = host
-            .default_input_device()
-            .ok_or("Nenhum dispositivo de entrada disponível")?;
+    /// Erro assíncrono do stream (ex.: dispositivo desconectado no meio da gravação)
+    pub fn error_handle(&self) -> Arc<Mutex<Option<AudioError>>> {
+        Arc::clone(&self.last_error)
+    }
 
-        println!("🎤 Dispositivo: {}", device.name().unwrap());
+    /// Inicia a gravação do microfone indicado (ou o padrão do sistema) e, se houver,
+    /// da fonte de loopback em paralelo. Sem `loopback_name`, tenta detectar um `.monitor`.
+    /// Chunks e limites de fala chegam por `Capture::events`; níveis por `Capture::meters`.
+    /// Com `archive_dir`, cada stream também é gravado inteiro em `<speaker>.flac` nesse diretório.
+    /// Com `pending_dir`, cada chunk é gravado lá ao fechar, antes de ser entregue.
+    pub fn start_recording(
+        &self,
+        device_name: Option<&str>,
+        loopback_name: Option<&str>,
+        options: &CaptureOptions,
+        archive_dir: Option<&Path>,
+        pending_dir: Option<&Path>,
+    ) -> Result<Capture, AudioError> {
+        let mic_device = resolve_input_device(device_name)?;
+        let loopback_device = match loopback_name {
+            Some(name) => Some(get_input_device_by_name(name)?),
+            None => find_loopback_device(),
+        };
+
+        let microphone = mic_device
+            .name()
+            .map_err(|e| AudioError::Stream(e.to_string()))?;
+        // Nunca abrir o mesmo dispositivo como microfone e loopback
+        let loopback_device = loopback_device
+            .and_then(|device| device.name().ok().map(|name| (device, name)))
+            .filter(|(_, name)| *name != microphone);
+        let mut loopback = loopback_device.as_ref().map(|(_, name)| name.clone());
+
+        let (events_tx, events) = mpsc::channel(EVENT_CHANNEL_CAPACITY);
+        let (partials_tx, partials) = mpsc::channel(CAPTURE_CHANNEL_CAPACITY);
+        let (meters_tx, meters) = mpsc::channel(CAPTURE_CHANNEL_CAPACITY);
+        for dir in archive_dir.iter().chain(pending_dir.iter()) {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| AudioError::Stream(format!("Erro ao criar diretório da sessão: {}", e)))?;
+        }
+        let outputs = StreamOutputs {
+            events: events_tx,
+            partials: partials_tx,
+            files: ChunkFiles {
+                save_dir: options.save_chunks.then(|| self.base_dir.clone()),
+                pending_dir: pending_dir.map(Path::to_path_buf),
+            },
+            meters: meters_tx,
+            archive_dir: archive_dir.map(Path::to_path_buf),
+        };
+
+        *self.last_error.lock().unwrap() = None;
+        *self.is_paused.lock().unwrap() = false;
+        *self.is_recording.lock().unwrap() = true;
+
+        if let Err(e) = self.spawn_stream(
+            mic_device,
+            microphone.clone(),
+            Speaker::Seller,
+            options,
+            outputs.clone(),
+        ) {
+            *self.is_recording.lock().unwrap() = false;
+            return Err(e);
+        }
+
+        match loopback_device {
+            Some((device, name)) => {
+                if let Err(e) = self.spawn_stream(device, name, Speaker::Customer, options, outputs) {
+                    // Só é erro se o usuário escolheu esse loopback; o detectado sozinho
+                    // pode nem servir para captura (ex.: monitor de uma saída desligada)
+                    if loopback_name.is_some() {
+                        *self.is_recording.lock().unwrap() = false;
+                        return Err(e);
+                    }
+                    eprintln!("⚠️ Loopback detectado não abriu ({}) - capturando apenas o microfone", e);
+                    loopback = None;
+                }
+            }
+            None => println!("⚠️ Nenhuma fonte de loopback encontrada - capturando apenas o microfone"),
+        }
+
+        Ok(Capture {
+            devices: CaptureDevices { microphone, loopback },
+            events,
+            partials,
+            meters,
+        })
+    }
+
+    /// Abre um stream de entrada em uma thread própria, enviando chunks marcados com `speaker`
+    fn spawn_stream(
+        &self,
+        device: cpal::Device,
+        device_name: String,
+        speaker: Speaker,
+        options: &CaptureOptions,
+        outputs: StreamOutputs,
+    ) -> Result<(), AudioError> {
+        println!("🎤 Dispositivo ({}): {}", speaker.as_str(), device_name);
 
         let config = device
             .default_input_config()
-            .map_err(|e| format!("Erro ao obter configuração: {}", e))?;
+            .map_err(|e| AudioError::Stream(format!("Erro ao obter configuração: {}", e)))?;
 
         println!("⚙️ Config: {:?}", config);
 
-        let spec = hound::WavSpec {
-            channels: config.channels(),
-            sample_rate: config.sample_rate().0,
-            bits_per_sample: 16,
-            sample_format: hound::SampleFormat::Int,
-        };
-
         let is_recording = Arc::clone(&self.is_recording);
         let is_recording_stream = Arc::clone(&self.is_recording);
-        let base_dir = self.base_dir.clone();
-        // ⚡ 1 segundo - ultra-responsivo, Groq é rápido o suficiente
-        let chunk_duration = 1u64;
+        let is_recording_error = Arc::clone(&self.is_recording);
+        let is_paused = Arc::clone(&self.is_paused);
+        let last_error = Arc::clone(&self.last_error);
+        let stream_device_name = device_name;
 
-        *is_recording.lock().unwrap() = true;
+        // Os chunks saem sempre em mono na taxa alvo, independente do dispositivo
+        let target_rate = options.target_sample_rate;
+        let mut converter = FormatConverter::new(config.channels(), config.sample_rate().0, target_rate);
+        let mut meter = LevelMeter::new(
+            speaker,
+            config.sample_rate().0,
+            config.channels(),
+            options.level.clone(),
+            outputs.meters,
+        );
+        let (disk_tx, disk_rx) = std::sync::mpsc::channel();
+        let disk_writer = spawn_disk_writer(disk_rx, outputs.events, outputs.files);
+        let segmenter = Arc::new(Mutex::new(Segmenter::new(
+            speaker,
+            target_rate,
+            1,
+            options.vad.clone(),
+            ChunkOutputs {
+                events: disk_tx,
+                partials: outputs.partials,
+            },
+        )));
+        // Gravação contínua da sessão; os offsets dos chunks apontam para dentro dela
+        let archive = outputs.archive_dir.and_then(|dir| {
+            let path = dir.join(format!("{}.flac", speaker.as_str()));
+            match FlacWriter::create(&path, target_rate) {
+                Ok(writer) => Some(writer),
+                Err(e) => {
+                    eprintln!("⚠️ Erro ao criar gravação {:?}: {}", path, e);
+                    None
+                }
+            }
+        });
+        let (archive, archive_writer) = match archive {
+            Some(writer) => {
+                let (tx, rx) = std::sync::mpsc::channel();
+                (Some(tx), Some(spawn_archive_writer(writer, rx)))
+            }
+            None => (None, None),
+        };
+
+        let sample_format = config.sample_format();
+        let stream_config: cpal::StreamConfig = config.into();
+        let (ready_tx, ready_rx) = std::sync::mpsc::channel::<Result<(), AudioError>>();
 
         std::thread::spawn(move || {
-            // Usar Arc<Mutex> para compartilhar o writer
-            let current_writer: Arc<Mutex<Option<hound::WavWriter<std::io::BufWriter<std::fs::File>>>>> = Arc::new(Mutex::new(None));
-            let current_writer_clone = Arc::clone(&current_writer);
-            
-            let samples_written = Arc::new(Mutex::new(0u64));
-            let samples_written_clone = Arc::clone(&samples_written);
-            
-            let samples_per_chunk = (config.sample_rate().0 as u64 * chunk_duration) * config.channels() as u64;
-
-            let device_clone = host.default_input_device().unwrap();
-            let config_clone = device_clone.default_input_config().unwrap();
-
-            let stream = device_clone
-                .build_input_stream(
-                    &config_clone.into(),
-                    move |data: &[f32], _: &cpal::InputCallbackInfo| {
-                        if !*is_recording_stream.lock().unwrap() {
-                            return;
-                        }
-
-                        let mut samples = samples_written_clone.lock().unwrap();
-                        let mut writer_guard = current_writer_clone.lock().unwrap();
-
-                        // Criar novo chunk se necessário
-                        if writer_guard.is_none() || *samples >= samples_per_chunk {
-                            // Fechar writer anterior
-                            if writer_guard.is_some() {
-                                *writer_guard = None;
-                                println!("✅ Chunk finalizado");
-                            }
-
-                            // Criar novo arquivo
-                            let timestamp = SystemTime::now()
-                                .duration_since(UNIX_EPOCH)
-                                .unwrap()
-                                .as_millis();
-                            
-                            let mut chunk_path = base_dir.clone();
-                            chunk_path.push(format!("chunk_{}.wav", timestamp));
-
-                            if let Ok(writer) = hound::WavWriter::create(&chunk_path, spec) {
-                                println!("🎙️ Novo chunk: {:?}", chunk_path);
-                                *writer_guard = Some(writer);
-                                *samples = 0;
-                            }
-                        }
-
-                        // Escrever samples
-                        if let Some(ref mut writer) = *writer_guard {
-                            for &sample in data {
-                                let amplitude = (sample * i16::MAX as f32) as i16;
-                                let _ = writer.write_sample(amplitude);
-                            }
-                            *samples += data.len() as u64;
-                        }
-                    },
-                    |err| eprintln!("❌ Erro no stream: {}", err),
-                    None,
-                )
-                .unwrap();
-
-            stream.play().unwrap();
+            let segmenter_stream = Arc::clone(&segmenter);
+            let mut was_paused = false;
+
+            let on_data = move |data: &[f32]| {
+                if !*is_recording_stream.lock().unwrap() {
+                    return;
+                }
+
+                // Em pausa o áudio não entra na linha do tempo; a fala em andamento
+                // vira chunk na hora de pausar
+                if *is_paused.lock().unwrap() {
+                    if !was_paused {
+                        was_paused = true;
+                        segmenter_stream.lock().unwrap().flush();
+                    }
+                    return;
+                }
+                was_paused = false;
+
+                meter.push(data);
+                let converted = converter.process(data);
+                segmenter_stream.lock().unwrap().push(&converted);
+                if let Some(archive) = &archive {
+                    // Falha só se a thread da gravação já parou por erro de disco
+                    let _ = archive.send(converted);
+                }
+            };
+            let on_error = move |err: cpal::StreamError| {
+                eprintln!("❌ Erro no stream: {}", err);
+                if let cpal::StreamError::DeviceNotAvailable = err {
+                    *last_error.lock().unwrap() =
+                        Some(AudioError::DeviceDisconnected(stream_device_name.clone()));
+                    *is_recording_error.lock().unwrap() = false;
+                }
+            };
+
+            // O stream não é Send: precisa ser criado e mantido nesta thread
+            let stream = match open_input_stream(&device, &stream_config, sample_format, on_data, on_error) {
+                Ok(stream) => stream,
+                Err(e) => {
+                    let _ = ready_tx.send(Err(e));
+                    return;
+                }
+            };
+
+            if let Err(e) = stream.play() {
+                let _ = ready_tx.send(Err(AudioError::Stream(format!("Erro ao iniciar stream: {}", e))));
+                return;
+            }
+            let _ = ready_tx.send(Ok(()));
             
             // Manter stream vivo
             while *is_recording.lock().unwrap() {
                 std::thread::sleep(std::time::Duration::from_millis(100));
             }
             
-            // Finalizar último chunk
-            *current_writer.lock().unwrap() = None;
-            println!("🛑 Gravação finalizada");
+            // Fechar o stream antes de entregar o último chunk; com ele vai o lado do
+            // callback do canal da gravação, que então fecha o arquivo
+            drop(stream);
+            segmenter.lock().unwrap().flush();
+            // Sem o segmentador, a thread de disco termina depois de gravar o que falta
+            drop(segmenter);
+            let _ = disk_writer.join();
+            if let Some(archive_writer) = archive_writer {
+                let _ = archive_writer.join();
+            }
+            println!("🛑 Gravação finalizada ({})", speaker.as_str());
         });
 
+        ready_rx
+            .recv()
+            .map_err(|_| AudioError::Stream("Thread de captura encerrou inesperadamente".to_string()))?
+    }
+
+    /// Para de alimentar o pipeline sem fechar os streams nem reiniciar a linha do tempo
+    pub fn pause_recording(&self) -> Result<(), String> {
+        if !*self.is_recording.lock().unwrap() {
+            return Err("Nenhuma gravação em andamento".to_string());
+        }
+        *self.is_paused.lock().unwrap() = true;
+        println!("⏸️ Gravação pausada");
+        Ok(())
+    }
+
+    pub fn resume_recording(&self) -> Result<(), String> {
+        if !*self.is_recording.lock().unwrap() {
+            return Err("Nenhuma gravação em andamento".to_string());
+        }
+        *self.is_paused.lock().unwrap() = false;
+        println!("▶️ Gravação retomada");
         Ok(())
     }
 
@@ -140,3 +1128,217 @@ impl AudioRecorder {
         Ok(())
     }
 }
+
+/// Divide um áudio já gravado (intercalado, em qualquer taxa) com a mesma conversão e VAD
+/// da captura ao vivo, um bloco por vez: só o chunk em construção fica na memória.
+/// Os limites de fala são descartados; só os chunks interessam aqui.
+pub struct RecordingSplitter {
+    options: CaptureOptions,
+    /// Conversor do formato atual (recriado se o arquivo mudar de taxa ou canais)
+    converter: Option<(u32, u16, FormatConverter)>,
+    segmenter: Segmenter,
+    events: std::sync::mpsc::Receiver<CaptureEvent>,
+}
+
+impl RecordingSplitter {
+    pub fn new(speaker: Speaker, options: &CaptureOptions) -> Self {
+        let (events_tx, events) = std::sync::mpsc::channel();
+        let (partials_tx, _) = mpsc::channel(1);
+        // Parciais só servem para a visualização ao vivo
+        let vad = VadOptions {
+            partial_interval_ms: 0,
+            ..options.vad.clone()
+        };
+        let outputs = ChunkOutputs {
+            events: events_tx,
+            partials: partials_tx,
+        };
+
+        RecordingSplitter {
+            options: options.clone(),
+            converter: None,
+            segmenter: Segmenter::new(speaker, options.target_sample_rate, 1, vad, outputs),
+            events,
+        }
+    }
+
+    /// Processa mais um bloco e devolve os chunks que fecharam com ele
+    pub fn push(&mut self, samples: &[f32], sample_rate: u32, channels: u16) -> Vec<AudioChunk> {
+        let target_rate = self.options.target_sample_rate;
+        let converter = match &mut self.converter {
+            Some((rate, count, converter)) if *rate == sample_rate && *count == channels => converter,
+            slot => &mut slot.insert((sample_rate, channels, FormatConverter::new(channels, sample_rate, target_rate))).2,
+        };
+        self.segmenter.push(&converter.process(samples));
+        self.drain()
+    }
+
+    /// Fecha a fala que estiver em andamento no fim da gravação
+    pub fn finish(mut self) -> Vec<AudioChunk> {
+        self.segmenter.flush();
+        self.drain()
+    }
+
+    fn drain(&mut self) -> Vec<AudioChunk> {
+        let mut chunks = Vec::new();
+        while let Ok(event) = self.events.try_recv() {
+            if let CaptureEvent::Chunk(chunk) = event {
+                chunks.push(chunk);
+            }
+        }
+        chunks
+    }
+}
+
+/// Abre o stream no formato nativo do dispositivo, convertendo cada callback para f32
+fn open_input_stream<D, E>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    sample_format: cpal::SampleFormat,
+    on_data: D,
+    on_error: E,
+) -> Result<cpal::Stream, AudioError>
+where
+    D: FnMut(&[f32]) + Send + 'static,
+    E: FnMut(cpal::StreamError) + Send + 'static,
+{
+    use cpal::SampleFormat;
+
+    let stream = match sample_format {
+        SampleFormat::F32 => build_converting_stream::<f32, _, _>(device, config, on_data, on_error),
+        SampleFormat::F64 => build_converting_stream::<f64, _, _>(device, config, on_data, on_error),
+        SampleFormat::I8 => build_converting_stream::<i8, _, _>(device, config, on_data, on_error),
+        SampleFormat::I16 => build_converting_stream::<i16, _, _>(device, config, on_data, on_error),
+        SampleFormat::I32 => build_converting_stream::<i32, _, _>(device, config, on_data, on_error),
+        SampleFormat::I64 => build_converting_stream::<i64, _, _>(device, config, on_data, on_error),
+        SampleFormat::U8 => build_converting_stream::<u8, _, _>(device, config, on_data, on_error),
+        SampleFormat::U16 => build_converting_stream::<u16, _, _>(device, config, on_data, on_error),
+        SampleFormat::U32 => build_converting_stream::<u32, _, _>(device, config, on_data, on_error),
+        SampleFormat::U64 => build_converting_stream::<u64, _, _>(device, config, on_data, on_error),
+        other => return Err(AudioError::UnsupportedFormat(format!("{:?}", other))),
+    };
+
+    stream.map_err(|e| AudioError::Stream(format!("Erro ao abrir stream ({:?}): {}", sample_format, e)))
+}
+
+fn build_converting_stream<T, D, E>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    mut on_data: D,
+    on_error: E,
+) -> Result<cpal::Stream, cpal::BuildStreamError>
+where
+    T: cpal::SizedSample,
+    f32: cpal::FromSample<T>,
+    D: FnMut(&[f32]) + Send + 'static,
+    E: FnMut(cpal::StreamError) + Send + 'static,
+{
+    let mut buffer: Vec<f32> = Vec::new();
+
+    device.build_input_stream(
+        config,
+        move |data: &[T], _: &cpal::InputCallbackInfo| {
+            buffer.clear();
+            buffer.extend(data.iter().map(|&sample| sample.to_sample::<f32>()));
+            on_data(&buffer);
+        },
+        on_error,
+        None,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TONE_HZ: f32 = 440.0;
+    const DURATION_SECS: usize = 2;
+
+    /// Seno de `TONE_HZ` com `channels` canais intercalados iguais
+    fn sine(sample_rate: u32, channels: u16) -> Vec<f32> {
+        (0..sample_rate as usize * DURATION_SECS)
+            .map(|i| 0.5 * (2.0 * std::f32::consts::PI * TONE_HZ * i as f32 / sample_rate as f32).sin())
+            .flat_map(|sample| std::iter::repeat_n(sample, channels as usize))
+            .collect()
+    }
+
+    /// Passa o sinal pelo conversor em blocos de `block_frames`, como os callbacks do cpal
+    fn convert(input: &[f32], sample_rate: u32, channels: u16, block_frames: usize) -> Vec<f32> {
+        let mut converter = FormatConverter::new(channels, sample_rate, WHISPER_SAMPLE_RATE);
+        input
+            .chunks(block_frames * channels as usize)
+            .flat_map(|block| converter.process(block))
+            .collect()
+    }
+
+    /// Frequência estimada pelas passagens por zero
+    fn zero_crossing_hz(samples: &[f32], sample_rate: u32) -> f32 {
+        let crossings = samples
+            .windows(2)
+            .filter(|pair| (pair[0] < 0.0) != (pair[1] < 0.0))
+            .count();
+        crossings as f32 / 2.0 / (samples.len() as f32 / sample_rate as f32)
+    }
+
+    fn assert_converted(output: &[f32]) {
+        let expected_len = WHISPER_SAMPLE_RATE as usize * DURATION_SECS;
+        assert!(
+            output.len().abs_diff(expected_len) <= 2,
+            "esperado ~{} samples mono a 16 kHz, veio {}",
+            expected_len,
+            output.len()
+        );
+
+        let hz = zero_crossing_hz(output, WHISPER_SAMPLE_RATE);
+        assert!((hz - TONE_HZ).abs() < TONE_HZ * 0.01, "frequência mudou: {} Hz", hz);
+
+        // A média móvel atenua um pouco, mas o tom continua lá
+        let peak = output.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+        assert!(peak > 0.4 && peak <= 0.5, "amplitude inesperada: {}", peak);
+    }
+
+    #[test]
+    fn converts_48khz_stereo_to_16khz_mono() {
+        let input = sine(48_000, 2);
+        // 10 ms por callback e um tamanho que não divide a razão 3:1
+        assert_converted(&convert(&input, 48_000, 2, 480));
+        assert_converted(&convert(&input, 48_000, 2, 1_021));
+    }
+
+    #[test]
+    fn converts_44_1khz_mono_to_16khz_mono() {
+        let input = sine(44_100, 1);
+        assert_converted(&convert(&input, 44_100, 1, 441));
+        assert_converted(&convert(&input, 44_100, 1, 512));
+    }
+
+    #[test]
+    fn splitter_cuts_speech_from_blocks() {
+        // Fala, pausa de 1 s e fala de novo, em 48 kHz estéreo
+        let tone = sine(48_000, 2);
+        let mut recording = tone.clone();
+        recording.extend(std::iter::repeat_n(0.0, 48_000 * 2));
+        recording.extend(&tone);
+
+        let mut splitter = RecordingSplitter::new(Speaker::Customer, &CaptureOptions::default());
+        let mut chunks: Vec<AudioChunk> = recording
+            .chunks(4_096)
+            .flat_map(|block| splitter.push(block, 48_000, 2))
+            .collect();
+        chunks.extend(splitter.finish());
+
+        assert_eq!(chunks.len(), 2);
+        for chunk in &chunks {
+            assert_eq!((chunk.sample_rate, chunk.channels), (WHISPER_SAMPLE_RATE, 1));
+            assert_eq!(chunk.samples.len() as u64, (chunk.end_ms - chunk.start_ms) * 16);
+        }
+        // A segunda fala começa em 3 s; o chunk inclui o padding antes dela
+        assert_eq!(chunks[1].start_ms, 3_000 - VadOptions::default().padding_ms);
+    }
+
+    #[test]
+    fn downmix_averages_channels() {
+        assert_eq!(downmix_to_mono(&[1.0, 0.0, 0.5, -0.5], 2), vec![0.5, 0.0]);
+        assert_eq!(downmix_to_mono(&[0.25, -0.25], 1), vec![0.25, -0.25]);
+    }
+}
@@ -1,10 +1,55 @@
 use cpal::traits::{DeviceTrait, HostTrait};
 use serde::{Deserialize, Serialize};
+use std::fmt;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AudioDeviceInfo {
     pub name: String,
     pub is_default: bool,
+    pub is_loopback: bool,
+}
+
+/// Trechos de nome que indicam uma fonte de monitor/loopback do sistema
+/// (PulseAudio/PipeWire `.monitor`, "Stereo Mix" do Windows, etc.)
+const LOOPBACK_HINTS: [&str; 5] = [".monitor", "monitor of", "stereo mix", "loopback", "what u hear"];
+
+/// Erros de captura devolvidos ao frontend com um `kind` estável
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", content = "message", rename_all = "snake_case")]
+pub enum AudioError {
+    /// O sistema não tem nenhum dispositivo de entrada
+    NoDefaultDevice,
+    /// O dispositivo escolhido não está mais na lista (desconectado)
+    DeviceNotFound(String),
+    /// O dispositivo sumiu com a gravação em andamento
+    DeviceDisconnected(String),
+    /// O dispositivo só oferece um formato de sample que não sabemos converter
+    UnsupportedFormat(String),
+    /// Falha ao obter configuração ou abrir o stream
+    Stream(String),
+    /// A captura, importação ou nova tentativa anterior ainda está transcrevendo
+    Busy(String),
+}
+
+impl fmt::Display for AudioError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AudioError::NoDefaultDevice => write!(f, "Nenhum dispositivo de entrada disponível"),
+            AudioError::DeviceNotFound(name) => write!(f, "Dispositivo '{}' não encontrado", name),
+            AudioError::DeviceDisconnected(name) => write!(f, "Dispositivo '{}' foi desconectado", name),
+            AudioError::UnsupportedFormat(format) => write!(f, "Formato de sample não suportado: {}", format),
+            AudioError::Stream(msg) => write!(f, "Erro no stream de áudio: {}", msg),
+            AudioError::Busy(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl std::error::Error for AudioError {}
+
+impl From<AudioError> for String {
+    fn from(err: AudioError) -> Self {
+        err.to_string()
+    }
 }
 
 /// Lista todos os dispositivos de entrada de áudio disponíveis
@@ -21,9 +66,11 @@ pub fn list_input_devices() -> Result<Vec<AudioDeviceInfo>, String> {
     for device in host.input_devices().map_err(|e| e.to_string())? {
         if let Ok(name) = device.name() {
             let is_default = Some(&name) == default_name.as_ref();
+            let is_loopback = is_loopback_name(&name);
             devices.push(AudioDeviceInfo {
                 name,
                 is_default,
+                is_loopback,
             });
         }
     }
@@ -32,10 +79,10 @@ pub fn list_input_devices() -> Result<Vec<AudioDeviceInfo>, String> {
 }
 
 /// Obtém um dispositivo de entrada pelo nome
-pub fn get_input_device_by_name(device_name: &str) -> Result<cpal::Device, String> {
+pub fn get_input_device_by_name(device_name: &str) -> Result<cpal::Device, AudioError> {
     let host = cpal::default_host();
     
-    for device in host.input_devices().map_err(|e| e.to_string())? {
+    for device in host.input_devices().map_err(|e| AudioError::Stream(e.to_string()))? {
         if let Ok(name) = device.name() {
             if name == device_name {
                 return Ok(device);
@@ -43,5 +90,33 @@ pub fn get_input_device_by_name(device_name: &str) -> Result<cpal::Device, Strin
         }
     }
     
-    Err(format!("Dispositivo '{}' não encontrado", device_name))
+    Err(AudioError::DeviceNotFound(device_name.to_string()))
+}
+
+/// Resolve o dispositivo escolhido pelo usuário, ou o padrão do sistema se nenhum foi escolhido
+pub fn resolve_input_device(device_name: Option<&str>) -> Result<cpal::Device, AudioError> {
+    match device_name {
+        Some(name) => get_input_device_by_name(name),
+        None => cpal::default_host()
+            .default_input_device()
+            .ok_or(AudioError::NoDefaultDevice),
+    }
+}
+
+/// Indica se o nome do dispositivo parece ser uma fonte de loopback
+pub fn is_loopback_name(name: &str) -> bool {
+    let lower = name.to_lowercase();
+    LOOPBACK_HINTS.iter().any(|hint| lower.contains(hint))
+}
+
+/// Procura a primeira fonte de loopback disponível para capturar o áudio do cliente
+pub fn find_loopback_device() -> Option<cpal::Device> {
+    let host = cpal::default_host();
+
+    host.input_devices().ok()?.find(|device| {
+        device
+            .name()
+            .map(|name| is_loopback_name(&name))
+            .unwrap_or(false)
+    })
 }
@@ -0,0 +1,99 @@
+use std::fs::File;
+use std::path::Path;
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::{Decoder, DecoderOptions, CODEC_TYPE_NULL};
+use symphonia::core::errors::Error as SymphoniaError;
+use symphonia::core::formats::{FormatOptions, FormatReader};
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+
+/// Trecho decodificado de um arquivo, com samples intercalados
+pub struct DecodedBlock {
+    pub samples: Vec<f32>,
+    pub sample_rate: u32,
+    pub channels: u16,
+}
+
+/// Decodifica um arquivo de áudio (WAV, FLAC, MP3, OGG...) pacote a pacote, para que
+/// gravações longas nunca fiquem inteiras na memória
+pub struct AudioDecoder {
+    format: Box<dyn FormatReader>,
+    decoder: Box<dyn Decoder>,
+    track_id: u32,
+    /// Duração informada pelo contêiner, quando há
+    pub duration_ms: Option<u64>,
+}
+
+impl AudioDecoder {
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let file = File::open(path).map_err(|e| format!("Erro ao abrir {:?}: {}", path, e))?;
+        let stream = MediaSourceStream::new(Box::new(file), Default::default());
+
+        let mut hint = Hint::new();
+        if let Some(extension) = path.extension().and_then(|s| s.to_str()) {
+            hint.with_extension(extension);
+        }
+
+        let probed = symphonia::default::get_probe()
+            .format(&hint, stream, &FormatOptions::default(), &MetadataOptions::default())
+            .map_err(|e| format!("Formato de áudio não reconhecido: {}", e))?;
+        let format = probed.format;
+
+        let track = format
+            .tracks()
+            .iter()
+            .find(|track| track.codec_params.codec != CODEC_TYPE_NULL)
+            .ok_or("Arquivo sem faixa de áudio")?;
+        let track_id = track.id;
+        let duration_ms = match (track.codec_params.n_frames, track.codec_params.sample_rate) {
+            (Some(frames), Some(rate)) if rate > 0 => Some(frames * 1000 / rate as u64),
+            _ => None,
+        };
+
+        let decoder = symphonia::default::get_codecs()
+            .make(&track.codec_params, &DecoderOptions::default())
+            .map_err(|e| format!("Codec não suportado: {}", e))?;
+
+        Ok(AudioDecoder {
+            format,
+            decoder,
+            track_id,
+            duration_ms,
+        })
+    }
+
+    /// Próximo pacote decodificado; `None` no fim do arquivo
+    pub fn next_block(&mut self) -> Result<Option<DecodedBlock>, String> {
+        loop {
+            let packet = match self.format.next_packet() {
+                Ok(packet) => packet,
+                // Fim do arquivo
+                Err(SymphoniaError::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+                Err(e) => return Err(format!("Erro ao ler áudio: {}", e)),
+            };
+            if packet.track_id() != self.track_id {
+                continue;
+            }
+
+            match self.decoder.decode(&packet) {
+                Ok(decoded) => {
+                    let spec = *decoded.spec();
+                    let mut buffer = SampleBuffer::<f32>::new(decoded.capacity() as u64, spec);
+                    buffer.copy_interleaved_ref(decoded);
+                    if spec.rate == 0 || buffer.samples().is_empty() {
+                        continue;
+                    }
+                    return Ok(Some(DecodedBlock {
+                        samples: buffer.samples().to_vec(),
+                        sample_rate: spec.rate,
+                        channels: spec.channels.count() as u16,
+                    }));
+                }
+                // Pacote corrompido: pula e segue com o resto do arquivo
+                Err(SymphoniaError::DecodeError(e)) => eprintln!("⚠️ Pacote de áudio ignorado: {}", e),
+                Err(e) => return Err(format!("Erro ao decodificar áudio: {}", e)),
+            }
+        }
+    }
+}
@@ -1,19 +1,528 @@
-use tauri::{AppHandle, Emitter, State};
+use tauri::{AppHandle, Emitter, Runtime, State, Wry};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
-use std::time::{SystemTime, UNIX_EPOCH};
-use crate::audio::AudioRecorder;
-use crate::audio_devices::{list_input_devices, AudioDeviceInfo};
-use crate::whisper::{WhisperService, TranscriptionResult};
-use crate::groq_whisper::GroqWhisperService;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::{mpsc, Semaphore};
+use tokio::task::JoinHandle;
+use crate::audio::{
+    AudioChunk, AudioRecorder, Capture, CaptureEvent, CaptureOptions, MeterEvent, RecordingSplitter, Speaker,
+};
+use crate::audio_devices::{get_input_device_by_name, list_input_devices, AudioDeviceInfo, AudioError};
+use crate::transcript::Transcript;
+use crate::whisper::WhisperService;
+use crate::whisper_api::WhisperApiService;
+use crate::transcription::{
+    load_whisper_cpp, transcribe_partial, transcribe_with_fallback, ProviderKind, Transcribers, TranscriptionFailure,
+    TranscriptionOptions, AUTO_LANGUAGE,
+};
+use crate::transcript_filter::{FilterOptions, FilterStats};
 use crate::llm::{OpenAIService, AnalysisResult};
-use crate::events::{TranscriptionEvent, AnalysisEvent};
+use crate::events::{
+    TranscriptionEvent, TranscriptionErrorEvent, AnalysisEvent, ChunkEvent, ImportProgress, LanguageChange,
+    PartialTranscriptionEvent,
+};
+use crate::audio_import::AudioDecoder;
+use crate::settings::Settings;
+use crate::session::{list_recordings, pending_chunk_path, pending_chunks, pending_dir, Session, SessionRecording};
 
 pub struct AppState {
     pub recorder: Mutex<AudioRecorder>,
-    pub whisper: Mutex<WhisperService>,
-    pub groq_whisper: Arc<Mutex<Option<GroqWhisperService>>>,
+    pub transcription: Arc<Mutex<Transcribers>>,
     pub llm: Arc<Mutex<Option<OpenAIService>>>,
     pub is_realtime: Arc<Mutex<bool>>,
+    /// Sessão da última captura ou importação. Cada execução grava só na sua; esta
+    /// cópia do `Arc` serve para consultar e ajustar a mais recente.
+    pub session: Mutex<Option<Arc<Mutex<Session>>>>,
+    /// Captura, importação ou nova tentativa com a fila de transcrição rodando, até
+    /// publicar e salvar o último chunk
+    pub running: Arc<Mutex<Option<&'static str>>>,
+    pub settings: Mutex<Settings>,
+    pub settings_path: PathBuf,
+    /// Uma pasta por sessão com as gravações completas e o `session.json`
+    pub sessions_dir: PathBuf,
+}
+
+impl AppState {
+    fn current_session(&self) -> Option<Arc<Mutex<Session>>> {
+        self.session.lock().unwrap().clone()
+    }
+}
+
+/// Marca de uma captura, importação ou nova tentativa em andamento; sai quando é descartada
+struct PipelineRun(Arc<Mutex<Option<&'static str>>>);
+
+impl PipelineRun {
+    /// Só uma por vez: a anterior pode estar drenando a fila de transcrição depois de parar
+    fn claim(state: &AppState, what: &'static str) -> Result<Self, String> {
+        let mut running = state.running.lock().unwrap();
+        if let Some(current) = *running {
+            return Err(format!("Aguarde: {} ainda está transcrevendo", current));
+        }
+        *running = Some(what);
+        Ok(PipelineRun(Arc::clone(&state.running)))
+    }
+}
+
+impl Drop for PipelineRun {
+    fn drop(&mut self) {
+        *self.0.lock().unwrap() = None;
+    }
+}
+
+/// Aplica uma alteração nas configurações e persiste em disco
+fn update_settings(state: &AppState, change: impl FnOnce(&mut Settings)) {
+    let mut settings = state.settings.lock().unwrap();
+    change(&mut settings);
+    if let Err(e) = settings.save(&state.settings_path) {
+        eprintln!("⚠️ {}", e);
+    }
+}
+
+/// Inicia a gravação com os dispositivos passados ou, na falta deles, os últimos escolhidos.
+/// Dispositivos passados explicitamente ficam salvos para as próximas sessões.
+/// Com `transcribe`, cada chunk fica nos pendentes da sessão até ser transcrito.
+fn start_recorder(
+    device_name: Option<String>,
+    loopback_device_name: Option<String>,
+    options: &CaptureOptions,
+    session_dir: &Path,
+    transcribe: bool,
+    state: &AppState,
+) -> Result<Capture, AudioError> {
+    let (saved_device, saved_loopback) = {
+        let settings = state.settings.lock().unwrap();
+        (settings.input_device.clone(), settings.loopback_device.clone())
+    };
+    let explicit_device = device_name.is_some();
+    let explicit_loopback = loopback_device_name.is_some();
+
+    let capture = state.recorder.lock().unwrap().start_recording(
+        device_name.or(saved_device).as_deref(),
+        loopback_device_name.or(saved_loopback).as_deref(),
+        options,
+        Some(session_dir),
+        transcribe.then(|| pending_dir(session_dir)).as_deref(),
+    )?;
+
+    if explicit_device || explicit_loopback {
+        update_settings(state, |settings| {
+            if explicit_device {
+                settings.input_device = Some(capture.devices.microphone.clone());
+            }
+            if explicit_loopback {
+                settings.loopback_device = capture.devices.loopback.clone();
+            }
+        });
+    }
+
+    Ok(capture)
+}
+
+/// Encerra o processo Python do backend local, que segura o modelo na memória. Espera a
+/// transcrição em andamento nele terminar; se outro chunk pedir depois, ele sobe de novo.
+async fn stop_local_whisper(local: Arc<Mutex<WhisperService>>) {
+    let stopped = tokio::task::spawn_blocking(move || local.lock().unwrap().stop_realtime()).await;
+    if let Ok(Err(e)) = stopped {
+        eprintln!("⚠️ {}", e);
+    }
+}
+
+/// Repassa a telemetria de nível da captura para a interface
+fn forward_meter_events(app: AppHandle, mut meters: mpsc::Receiver<MeterEvent>) {
+    tokio::spawn(async move {
+        while let Some(event) = meters.recv().await {
+            let _ = match event {
+                MeterEvent::Level(level) => app.emit("audio-level", level),
+                MeterEvent::Clipping(level) => app.emit("clipping-detected", level),
+                MeterEvent::Silent(silence) => app.emit("input-silent", silence),
+            };
+        }
+    });
+}
+
+/// Chunks de uma importação já segmentados e aguardando a fila de transcrição
+const IMPORT_CHUNK_BUFFER: usize = 4;
+
+/// Análises do LLM ao mesmo tempo por execução; as dos chunks seguintes esperam a vez
+const MAX_CONCURRENT_ANALYSES: usize = 2;
+
+/// Chunks seguidos com outro idioma até a sessão considerar que o speaker mudou de língua
+const LANGUAGE_CONFIRMATIONS: u32 = 3;
+
+/// Transcrição e análise de um chunk, compartilhadas pela captura ao vivo e pela importação.
+/// Genérico no runtime só para os testes rodarem sem janela (`tauri::test`).
+struct ChunkPipeline<R: Runtime = Wry> {
+    app: AppHandle<R>,
+    transcription: Arc<Mutex<Transcribers>>,
+    llm: Arc<Mutex<Option<OpenAIService>>>,
+    /// Sessão desta execução: o que ainda chega depois de parar não vai para a próxima
+    session: Arc<Mutex<Session>>,
+    /// Diretório da sessão, onde ficam os chunks ainda não transcritos
+    session_dir: PathBuf,
+    /// Vagas de análise, para uma conversa longa não disparar uma requisição por chunk
+    analyses: Arc<Semaphore>,
+}
+
+impl<R: Runtime> Clone for ChunkPipeline<R> {
+    fn clone(&self) -> Self {
+        ChunkPipeline {
+            app: self.app.clone(),
+            transcription: Arc::clone(&self.transcription),
+            llm: Arc::clone(&self.llm),
+            session: Arc::clone(&self.session),
+            session_dir: self.session_dir.clone(),
+            analyses: Arc::clone(&self.analyses),
+        }
+    }
+}
+
+impl<R: Runtime> ChunkPipeline<R> {
+    fn new(app: AppHandle<R>, state: &AppState, session: &Arc<Mutex<Session>>, session_dir: &Path) -> Self {
+        ChunkPipeline {
+            app,
+            transcription: Arc::clone(&state.transcription),
+            llm: Arc::clone(&state.llm),
+            session: Arc::clone(session),
+            session_dir: session_dir.to_path_buf(),
+            analyses: Arc::new(Semaphore::new(MAX_CONCURRENT_ANALYSES)),
+        }
+    }
+
+    /// Registra o chunk na linha do tempo da sessão, assim que ele chega
+    fn register_chunk(&self, chunk: &AudioChunk) {
+        let chunk_event = ChunkEvent::from(chunk);
+        self.session.lock().unwrap().timeline.push(chunk_event.clone());
+        let _ = self.app.emit("new-chunk", chunk_event);
+    }
+
+    /// Guarda o chunk nos pendentes da sessão, se a captura ainda não o gravou lá.
+    /// Ele fica em disco até ser transcrito, para poder ser repetido depois.
+    fn save_pending(&self, chunk: &AudioChunk) {
+        let pending_path = pending_chunk_path(&self.session_dir, chunk);
+        if pending_path.exists() {
+            return;
+        }
+        let saved = std::fs::create_dir_all(pending_dir(&self.session_dir))
+            .map_err(|e| format!("Erro ao criar diretório de pendentes: {}", e))
+            .and_then(|_| chunk.write_wav(&pending_path));
+        if let Err(e) = saved {
+            eprintln!("⚠️ {}", e);
+        }
+    }
+
+    /// Transcreve o chunk no backend escolhido; `None` se falhou ou não havia fala.
+    /// Sucesso tira o chunk dos pendentes.
+    async fn transcribe(&self, chunk: &AudioChunk) -> Option<Transcript> {
+        let pending_path = pending_chunk_path(&self.session_dir, chunk);
+        let transcript = match transcribe_with_fallback(&self.transcription, chunk).await {
+            Ok(transcript) => transcript,
+            Err(failure) => {
+                self.report_failure(chunk, &pending_path, failure);
+                return None;
+            }
+        };
+        let _ = std::fs::remove_file(&pending_path);
+        
+        // ⚠️ Ignorar apenas transcrições completamente vazias
+        if transcript.text.trim().is_empty() {
+            return None;
+        }
+        Some(transcript)
+    }
+
+    fn report_failure(&self, chunk: &AudioChunk, pending_path: &Path, failure: TranscriptionFailure) {
+        eprintln!("❌ Chunk {} ms ({}) não transcrito: {}", chunk.start_ms, chunk.speaker.as_str(), failure.error);
+
+        let mut chunk_event = ChunkEvent::from(chunk);
+        if pending_path.exists() {
+            chunk_event.path = Some(pending_path.to_string_lossy().to_string());
+        }
+        let _ = self.app.emit("transcription-error", TranscriptionErrorEvent {
+            chunk: chunk_event,
+            provider: failure.provider.map(str::to_string),
+            error: failure.error,
+            attempts: failure.attempts,
+        });
+    }
+
+    /// Filtro de alucinações, com as contagens na sessão. `false` se nada sobrou.
+    fn filter(&self, options: &FilterOptions, chunk: &AudioChunk, transcript: &mut Transcript) -> bool {
+        options.apply(chunk, transcript, &mut self.session.lock().unwrap().filter_stats)
+    }
+
+    /// Guarda o idioma detectado de cada speaker na sessão e avisa a interface quando ele
+    /// muda no meio da chamada. A detecção em chunks curtos oscila, então um idioma novo
+    /// só vale depois de `LANGUAGE_CONFIRMATIONS` chunks seguidos com ele (`candidates`
+    /// guarda o candidato de cada speaker e quantas vezes já apareceu).
+    fn track_language(
+        &self,
+        chunk: &AudioChunk,
+        transcript: &Transcript,
+        candidates: &mut HashMap<Speaker, (String, u32)>,
+    ) {
+        let Some(language) = transcript.language.clone() else {
+            return;
+        };
+
+        let previous = {
+            let mut session = self.session.lock().unwrap();
+            let current = session.detected_languages.get(&chunk.speaker);
+            if current == Some(&language) {
+                candidates.remove(&chunk.speaker);
+                return;
+            }
+            // O primeiro idioma do speaker vale na hora: não há o que trocar
+            if current.is_some() {
+                let candidate = candidates.entry(chunk.speaker).or_insert_with(|| (language.clone(), 0));
+                if candidate.0 != language {
+                    *candidate = (language.clone(), 0);
+                }
+                candidate.1 += 1;
+                if candidate.1 < LANGUAGE_CONFIRMATIONS {
+                    return;
+                }
+                candidates.remove(&chunk.speaker);
+            }
+            session.detected_languages.insert(chunk.speaker, language.clone())
+        };
+
+        if let Some(from) = previous {
+            println!("🌐 Idioma mudou: {} → {}", from, language);
+            let _ = self.app.emit("language-changed", LanguageChange {
+                speaker: chunk.speaker,
+                from,
+                to: language,
+                at_ms: chunk.start_ms,
+            });
+        }
+    }
+
+    /// Publica a transcrição e dispara a análise em paralelo.
+    /// Devolve a tarefa da análise.
+    fn deliver(&self, chunk: &AudioChunk, timestamp: u64, transcript: Transcript) -> JoinHandle<()> {
+        let text = transcript.text.clone();
+        println!("📝 Texto transcrito: {}", text);
+        
+        let event = TranscriptionEvent {
+            timestamp,
+            speaker: chunk.speaker,
+            start_ms: chunk.start_ms,
+            end_ms: chunk.end_ms,
+            utterance_id: chunk.utterance_id(),
+            transcript,
+        };
+        
+        self.session.lock().unwrap().transcript.push(event.clone());
+        
+        println!("🔔 Emitindo evento de transcrição");
+        let _ = self.app.emit("transcription-final", event.clone());
+        let _ = self.app.emit("new-transcription", event);
+        
+        // ⚡ Análise em paralelo - não bloqueia transcrições
+        let pipeline = self.clone();
+        
+        tokio::spawn(async move {
+            let Ok(_permit) = pipeline.analyses.acquire().await else {
+                return;
+            };
+            let llm_option = {
+                let llm_guard = pipeline.llm.lock().unwrap();
+                llm_guard.clone()
+            };
+            
+            if let Some(llm) = llm_option {
+                println!("Analisando...");
+                
+                match llm.analyze_transcription(&text).await {
+                    Ok(analysis) => {
+                        println!("✅ Análise concluída com sucesso");
+                        
+                        let analysis_event = AnalysisEvent {
+                            objections: analysis.objections,
+                            important_points: analysis.important_points,
+                            sentiment: analysis.sentiment,
+                            suggestions: analysis.suggestions,
+                        };
+                        
+                        pipeline.session.lock().unwrap().merge_analysis(&analysis_event);
+                        
+                        println!("🔔 Emitindo evento de análise");
+                        let _ = pipeline.app.emit("new-analysis", analysis_event);
+                    }
+                    Err(e) => {
+                        eprintln!("❌ Erro na análise: {}", e);
+                    }
+                }
+            }
+        })
+    }
+
+    /// Chunk que não rendeu texto: um `transcription-final` vazio tira os parciais dele da tela
+    fn discard(&self, chunk: &AudioChunk, timestamp: u64) {
+        let _ = self.app.emit("transcription-final", TranscriptionEvent {
+            timestamp,
+            speaker: chunk.speaker,
+            start_ms: chunk.start_ms,
+            end_ms: chunk.end_ms,
+            utterance_id: chunk.utterance_id(),
+            transcript: Transcript::default(),
+        });
+    }
+}
+
+/// Chunk já enviado ao backend, aguardando sua vez de ser publicado
+struct PendingChunk {
+    chunk: AudioChunk,
+    timestamp: u64,
+    transcript: JoinHandle<Option<Transcript>>,
+}
+
+/// Parciais de cada speaker
+#[derive(Default)]
+struct Partials {
+    /// Speakers com um parcial no backend; os próximos parciais deles são pulados
+    in_flight: HashSet<Speaker>,
+    /// Início do último chunk fechado de cada speaker: parciais até ali chegaram tarde
+    closed: HashMap<Speaker, u64>,
+}
+
+/// Fila de transcrição: até `concurrency` chunks no backend ao mesmo tempo, com os
+/// resultados publicados estritamente na ordem de captura. Um chunk lento segura só a
+/// publicação dos seguintes, não as requisições deles.
+struct TranscriptionQueue<R: Runtime = Wry> {
+    pipeline: ChunkPipeline<R>,
+    permits: Arc<Semaphore>,
+    pending: mpsc::Sender<PendingChunk>,
+    partials: Arc<Mutex<Partials>>,
+    filter: FilterOptions,
+}
+
+impl<R: Runtime> TranscriptionQueue<R> {
+    /// Inicia a tarefa que publica os resultados em ordem. `on_delivered` roda após cada
+    /// chunk (com ou sem texto); a tarefa termina quando a fila é descartada e devolve
+    /// as análises disparadas.
+    fn start(
+        pipeline: ChunkPipeline<R>,
+        options: TranscriptionOptions,
+        mut on_delivered: impl FnMut(&AudioChunk) + Send + 'static,
+    ) -> (Self, JoinHandle<Vec<JoinHandle<()>>>) {
+        let concurrency = options.concurrency.max(1);
+        let filter = options.filter.clone();
+        // Limita também quantos resultados prontos podem esperar um chunk lento
+        let (pending, mut ordered) = mpsc::channel::<PendingChunk>(concurrency * 4);
+
+        let delivery_pipeline = pipeline.clone();
+        let delivery = tokio::spawn(async move {
+            let mut analyses = Vec::new();
+            // Último trecho publicado de cada speaker e onde o áudio dele terminou
+            let mut last_delivered: HashMap<Speaker, (Transcript, u64)> = HashMap::new();
+            let mut language_candidates = HashMap::new();
+            // O canal preserva a ordem de chegada: esperar cada tarefa na vez dela é o reordenamento
+            while let Some(pending) = ordered.recv().await {
+                let transcript = pending.transcript.await.unwrap_or_else(|e| {
+                    eprintln!("❌ Tarefa de transcrição falhou: {}", e);
+                    None
+                });
+                let chunk = &pending.chunk;
+                let mut delivered = false;
+                if let Some(mut transcript) = transcript {
+                    if delivery_pipeline.filter(&options.filter, chunk, &mut transcript) {
+                        // Chunk sobreposto ao anterior: tirar as palavras repetidas na emenda
+                        if let Some((previous, previous_end_ms)) = last_delivered.get(&chunk.speaker) {
+                            if chunk.start_ms < *previous_end_ms {
+                                transcript.stitch_after(previous, *previous_end_ms);
+                            }
+                        }
+                        if !transcript.text.is_empty() {
+                            delivery_pipeline.track_language(chunk, &transcript, &mut language_candidates);
+                            last_delivered.insert(chunk.speaker, (transcript.clone(), chunk.end_ms));
+                            analyses.push(delivery_pipeline.deliver(chunk, pending.timestamp, transcript));
+                            delivered = true;
+                        }
+                    }
+                }
+                if !delivered {
+                    delivery_pipeline.discard(chunk, pending.timestamp);
+                }
+                on_delivered(chunk);
+            }
+            analyses
+        });
+
+        let queue = TranscriptionQueue {
+            pipeline,
+            permits: Arc::new(Semaphore::new(concurrency)),
+            pending,
+            partials: Arc::default(),
+            filter,
+        };
+        (queue, delivery)
+    }
+
+    /// Registra o chunk, já salvo nos pendentes, e o coloca para transcrever. Só espera
+    /// quando há resultados demais acumulados atrás de um chunk lento.
+    async fn push(&self, chunk: AudioChunk, timestamp: u64) {
+        // Daqui em diante o texto desse trecho vem do `transcription-final`
+        self.partials.lock().unwrap().closed.insert(chunk.speaker, chunk.start_ms);
+        self.pipeline.save_pending(&chunk);
+        self.pipeline.register_chunk(&chunk);
+        self.enqueue(chunk, timestamp).await;
+    }
+
+    /// Coloca para transcrever um chunk que já está na linha do tempo da sessão
+    async fn enqueue(&self, chunk: AudioChunk, timestamp: u64) {
+        let pipeline = self.pipeline.clone();
+        let permits = Arc::clone(&self.permits);
+        let task_chunk = chunk.clone();
+        let transcript = tokio::spawn(async move {
+            let _permit = permits.acquire_owned().await.ok()?;
+            pipeline.transcribe(&task_chunk).await
+        });
+
+        let _ = self.pending.send(PendingChunk { chunk, timestamp, transcript }).await;
+    }
+
+    /// Transcreve em segundo plano o chunk ainda aberto e emite `transcription-partial`.
+    /// Fora da fila e sem esperar vaga: enquanto um parcial do speaker está no backend,
+    /// os seguintes são pulados. Falhas só são registradas no log.
+    fn partial(&self, chunk: AudioChunk) {
+        if !self.partials.lock().unwrap().in_flight.insert(chunk.speaker) {
+            return;
+        }
+
+        let pipeline = self.pipeline.clone();
+        let partials = Arc::clone(&self.partials);
+        let filter = self.filter.clone();
+        tokio::spawn(async move {
+            let result = transcribe_partial(&pipeline.transcription, &chunk).await;
+
+            let mut partials = partials.lock().unwrap();
+            partials.in_flight.remove(&chunk.speaker);
+            let mut transcript = match result {
+                Ok(transcript) => transcript,
+                Err(e) => {
+                    eprintln!("⚠️ Parcial {} não transcrito: {}", chunk.utterance_id(), e);
+                    return;
+                }
+            };
+            // O chunk fechou enquanto o parcial estava no backend
+            if partials.closed.get(&chunk.speaker).is_some_and(|&start| chunk.start_ms <= start) {
+                return;
+            }
+            if !filter.apply(&chunk, &mut transcript, &mut FilterStats::default())
+                || transcript.text.trim().is_empty()
+            {
+                return;
+            }
+
+            let _ = pipeline.app.emit("transcription-partial", PartialTranscriptionEvent {
+                utterance_id: chunk.utterance_id(),
+                speaker: chunk.speaker,
+                transcript,
+            });
+        });
+    }
 }
 
 #[tauri::command]
@@ -21,8 +530,10 @@ pub async fn initialize_openai(
     api_key: String,
     state: State<'_, AppState>,
 ) -> Result<String, String> {
-    let openai = OpenAIService::new(api_key);
+    let openai = OpenAIService::new(api_key.clone());
     *state.llm.lock().unwrap() = Some(openai);
+    // A mesma chave habilita o Whisper da OpenAI como backend de transcrição
+    state.transcription.lock().unwrap().openai = Some(WhisperApiService::openai(api_key));
     Ok("OpenAI inicializado".to_string())
 }
 
@@ -31,17 +542,115 @@ pub async fn initialize_groq_whisper(
     api_key: String,
     state: State<'_, AppState>,
 ) -> Result<String, String> {
-    let groq = GroqWhisperService::new(api_key);
-    *state.groq_whisper.lock().unwrap() = Some(groq);
+    state.transcription.lock().unwrap().groq = Some(WhisperApiService::groq(api_key));
     Ok("Groq Whisper inicializado".to_string())
 }
 
+#[tauri::command]
+pub async fn get_transcription_provider(state: State<'_, AppState>) -> Result<ProviderKind, String> {
+    Ok(state.transcription.lock().unwrap().kind)
+}
+
+/// Troca o backend de transcrição; vale para os próximos chunks, inclusive da sessão atual
+#[tauri::command]
+pub async fn set_transcription_provider(
+    provider: ProviderKind,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let unused_local = {
+        let mut transcription = state.transcription.lock().unwrap();
+        transcription.set_primary(provider);
+        (!transcription.uses(ProviderKind::Local)).then(|| Arc::clone(&transcription.local))
+    };
+    update_settings(&state, |settings| settings.transcription_provider = provider);
+    if let Some(local) = unused_local {
+        stop_local_whisper(local).await;
+    }
+    Ok("Backend de transcrição selecionado".to_string())
+}
+
+/// Idioma das próximas transcrições: código ISO 639-1 (`pt`, `en`...) ou `auto` para
+/// detectar. Vale também para a sessão em andamento.
+#[tauri::command]
+pub async fn set_transcription_language(language: String, state: State<'_, AppState>) -> Result<String, String> {
+    let language = language.trim().to_lowercase();
+    let valid = language == AUTO_LANGUAGE
+        || ((2..=3).contains(&language.len()) && language.chars().all(|c| c.is_ascii_lowercase()));
+    if !valid {
+        return Err(format!("Idioma inválido: {} (use um código como \"pt\" ou \"auto\")", language));
+    }
+
+    state.transcription.lock().unwrap().hints.set_language(&language);
+    if let Some(session) = state.current_session() {
+        session.lock().unwrap().language = language.clone();
+    }
+    update_settings(&state, |settings| settings.transcription.language = language);
+    Ok("Idioma da transcrição atualizado".to_string())
+}
+
+#[tauri::command]
+pub async fn get_glossary(state: State<'_, AppState>) -> Result<Vec<String>, String> {
+    Ok(state.transcription.lock().unwrap().hints.glossary.clone())
+}
+
+/// Troca o glossário: enviado como `prompt` aos backends que aceitam e usado para
+/// corrigir a grafia nos demais. Vale a partir do próximo chunk.
+#[tauri::command]
+pub async fn set_glossary(terms: Vec<String>, state: State<'_, AppState>) -> Result<String, String> {
+    let mut glossary: Vec<String> = Vec::new();
+    for term in terms.iter().map(|t| t.trim()).filter(|t| !t.is_empty()) {
+        if !glossary.iter().any(|existing| existing.eq_ignore_ascii_case(term)) {
+            glossary.push(term.to_string());
+        }
+    }
+
+    state.transcription.lock().unwrap().hints.glossary = glossary.clone();
+    let count = glossary.len();
+    update_settings(&state, |settings| settings.transcription.glossary = glossary);
+    Ok(format!("Glossário com {} termos", count))
+}
+
+/// Backends usados, em ordem, quando o principal falha
+#[tauri::command]
+pub async fn set_transcription_fallback(
+    providers: Vec<ProviderKind>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let unused_local = {
+        let mut transcription = state.transcription.lock().unwrap();
+        transcription.fallback = providers.clone();
+        (!transcription.uses(ProviderKind::Local)).then(|| Arc::clone(&transcription.local))
+    };
+    update_settings(&state, |settings| settings.transcription.fallback = providers);
+    if let Some(local) = unused_local {
+        stop_local_whisper(local).await;
+    }
+    Ok("Fallback de transcrição atualizado".to_string())
+}
+
+/// Carrega um modelo GGML para o backend `whispercpp` e lembra o caminho
+#[tauri::command]
+pub async fn set_whisper_cpp_model(model_path: String, state: State<'_, AppState>) -> Result<String, String> {
+    let path = PathBuf::from(model_path);
+    let transcription = Arc::clone(&state.transcription);
+    let model = path.clone();
+    tokio::task::spawn_blocking(move || load_whisper_cpp(&transcription, &model))
+        .await
+        .map_err(|e| format!("Erro ao carregar modelo: {}", e))??;
+
+    update_settings(&state, |settings| settings.whisper_cpp_model = Some(path));
+    Ok("Modelo whisper.cpp carregado".to_string())
+}
+
 #[tauri::command]
 pub async fn start_realtime_capture(
     app: AppHandle,
+    device_name: Option<String>,
+    loopback_device_name: Option<String>,
     state: State<'_, AppState>,
-) -> Result<String, String> {
-    let base_dir = {
+) -> Result<String, AudioError> {
+    let run = PipelineRun::claim(&state, "a captura em tempo real").map_err(AudioError::Busy)?;
+    let device_error = {
         let recorder = state.recorder.lock().unwrap();
         let dir = recorder.get_base_dir();
         
@@ -49,139 +658,107 @@ pub async fn start_realtime_capture(
         if let Ok(entries) = std::fs::read_dir(&dir) {
             for entry in entries.flatten() {
                 let path = entry.path();
-                if path.extension().and_then(|s| s.to_str()) == Some("wav") {
+                let is_chunk = path.file_name().and_then(|s| s.to_str()).is_some_and(|name| name.starts_with("chunk_"));
+                if is_chunk && path.extension().and_then(|s| s.to_str()) == Some("wav") {
                     let _ = std::fs::remove_file(path);
                 }
             }
             println!("🧹 Chunks antigos removidos");
         }
         
-        recorder.start_recording()?;
-        dir
+        recorder.error_handle()
     };
+
+    let (options, language) = {
+        let settings = state.settings.lock().unwrap();
+        (settings.capture.clone(), settings.transcription.language.clone())
+    };
+    let session = Session::new(language);
+    let session_dir = state.sessions_dir.join(&session.id);
+    let capture = start_recorder(device_name, loopback_device_name, &options, &session_dir, true, &state)?;
+    let mut events = capture.events;
+    let mut partials = capture.partials;
+    forward_meter_events(app.clone(), capture.meters);
     
+    let session = Arc::new(Mutex::new(session));
     *state.is_realtime.lock().unwrap() = true;
+    *state.session.lock().unwrap() = Some(Arc::clone(&session));
+    
+    // Carregar o modelo local já, em vez de no primeiro chunk
+    let (kind, local_whisper) = {
+        let transcription = state.transcription.lock().unwrap();
+        (transcription.kind, Arc::clone(&transcription.local))
+    };
+    if kind == ProviderKind::Local {
+        tokio::task::spawn_blocking(move || {
+            if let Err(e) = local_whisper.lock().unwrap().start_realtime() {
+                eprintln!("❌ {}", e);
+            }
+        });
+    }
     
     let app_clone = app.clone();
-    let base_dir_clone = base_dir.clone();
     let is_realtime_clone = Arc::clone(&state.is_realtime);
-    let groq_whisper_clone = Arc::clone(&state.groq_whisper);
-    let llm_clone = Arc::clone(&state.llm);
+    let transcription = Arc::clone(&state.transcription);
+    let options = state.settings.lock().unwrap().transcription.clone();
+    let pipeline = ChunkPipeline::new(app.clone(), &state, &session, &session_dir);
+    let (queue, delivery) = TranscriptionQueue::start(pipeline, options, |_| {});
     
     tokio::spawn(async move {
-        let mut processed_chunks = std::collections::HashSet::new();
-        let start_time = SystemTime::now();
-        
+        // O canal fecha sozinho quando a captura termina
         loop {
-            if !*is_realtime_clone.lock().unwrap() {
-                break;
-            }
-            
-            if let Ok(entries) = std::fs::read_dir(&base_dir_clone) {
-                for entry in entries.flatten() {
-                    let path = entry.path();
-                    if path.extension().and_then(|s| s.to_str()) == Some("wav") {
-                        // Verificar se o arquivo foi criado DEPOIS de iniciar
-                        if let Ok(metadata) = path.metadata() {
-                            if let Ok(created) = metadata.created() {
-                                if created < start_time {
-                                    continue; // Pular chunks antigos
-                                }
-                            }
-                        }
-                        
-                        let path_str = path.to_string_lossy().to_string();
-                        
-                        if !processed_chunks.contains(&path_str) {
-                            processed_chunks.insert(path_str.clone());
-                            
-                            let timestamp = SystemTime::now()
-                                .duration_since(UNIX_EPOCH)
-                                .unwrap()
-                                .as_secs();
-                            
-                            let _ = app_clone.emit("new-chunk", path_str.clone());
-                            
-                            // ⚡ REMOVIDO DELAY - processamento instantâneo!
-                            
-                            // Usar Groq para transcrição ultra-rápida
-                            let transcription_result = {
-                                let groq_guard = groq_whisper_clone.lock().unwrap();
-                                if let Some(ref groq) = *groq_guard {
-                                    println!("🔄 Chamando Groq para: {}", path_str);
-                                    groq.transcribe_file(&path_str)
-                                } else {
-                                    println!("❌ Groq não inicializado!");
-                                    Err("Groq não inicializado".to_string())
-                                }
-                            };
-                            
-                            println!("🔍 Resultado da transcrição: {:?}", transcription_result);
-                            
-                            if let Ok(result) = transcription_result {
-                                if result.success && result.full_text.is_some() {
-                                    let text = result.full_text.unwrap();
-                                    
-                                    // ⚠️ Ignorar apenas transcrições completamente vazias
-                                    if text.trim().is_empty() {
-                                        continue;
-                                    }
-                                    
-                                    println!("📝 Texto transcrito: {}", text);
-                                    
-                                    let event = TranscriptionEvent {
-                                        text: text.clone(),
-                                        timestamp,
-                                        speaker: "user".to_string(),
-                                    };
-                                    
-                                    println!("🔔 Emitindo evento de transcrição");
-                                    let _ = app_clone.emit("new-transcription", event);
-                                    
-                                    // ⚡ Análise em paralelo - não bloqueia transcrições
-                                    let app_clone_analysis = app_clone.clone();
-                                    let llm_clone_analysis = Arc::clone(&llm_clone);
-                                    let text_clone = text.clone();
-                                    
-                                    tokio::spawn(async move {
-                                        let llm_option = {
-                                            let llm_guard = llm_clone_analysis.lock().unwrap();
-                                            llm_guard.clone()
-                                        };
-                                        
-                                        if let Some(llm) = llm_option {
-                                            println!("Analisando...");
-                                            
-                                            match llm.analyze_transcription(&text_clone).await {
-                                                Ok(analysis) => {
-                                                    println!("✅ Análise concluída com sucesso");
-                                                    
-                                                    let analysis_event = AnalysisEvent {
-                                                        objections: analysis.objections,
-                                                        important_points: analysis.important_points,
-                                                        sentiment: analysis.sentiment,
-                                                        suggestions: analysis.suggestions,
-                                                    };
-                                                    
-                                                    println!("🔔 Emitindo evento de análise");
-                                                    let _ = app_clone_analysis.emit("new-analysis", analysis_event);
-                                                }
-                                                Err(e) => {
-                                                    eprintln!("❌ Erro na análise: {}", e);
-                                                }
-                                            }
-                                        }
-                                    });
-                                }
-                            }
-                        }
-                    }
+            let event = tokio::select! {
+                // Chunks primeiro: parciais atrasados podem ser descartados
+                biased;
+                event = events.recv() => match event {
+                    Some(event) => event,
+                    None => break,
+                },
+                Some(partial) = partials.recv() => {
+                    queue.partial(partial);
+                    continue;
                 }
-            }
+            };
+            let chunk = match event {
+                CaptureEvent::Chunk(chunk) => chunk,
+                CaptureEvent::Speech(boundary) => {
+                    let name = if boundary.speaking { "speech-started" } else { "speech-ended" };
+                    let _ = app_clone.emit(name, boundary);
+                    continue;
+                }
+            };
+
+            let timestamp = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_secs();
             
-            // ⚡ Polling ultra-rápido - 100ms para detecção quase instantânea
-            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+            queue.push(chunk, timestamp).await;
+        }
+        // Dispositivo desconectado no meio da sessão
+        let stream_error = device_error.lock().unwrap().take();
+        if let Some(err) = stream_error {
+            eprintln!("❌ {}", err);
+            let _ = app_clone.emit("audio-device-error", err);
         }
+        *is_realtime_clone.lock().unwrap() = false;
+        
+        // Publicar o que ainda está em transcrição antes de salvar a sessão
+        drop(queue);
+        if let Ok(analyses) = delivery.await {
+            for analysis in analyses {
+                let _ = analysis.await;
+            }
+        }
+        
+        if let Err(e) = session.lock().unwrap().save(&session_dir) {
+            eprintln!("⚠️ {}", e);
+        }
+
+        // Sessão encerrada e tudo transcrito: o modelo local não precisa ficar na memória
+        let local = Arc::clone(&transcription.lock().unwrap().local);
+        stop_local_whisper(local).await;
+        drop(run);
         
         println!("Thread finalizada");
     });
@@ -189,6 +766,149 @@ pub async fn start_realtime_capture(
     Ok("Real-time iniciado".to_string())
 }
 
+/// Passa uma gravação de chamada (WAV/FLAC/MP3) pelo mesmo fluxo da captura ao vivo:
+/// chunks por VAD, transcrição e análise, com os mesmos eventos e `import-progress`.
+/// O arquivo tem um lado só; sem `speaker`, a fala é atribuída ao cliente.
+#[tauri::command]
+pub async fn import_recording(
+    app: AppHandle,
+    path: String,
+    speaker: Option<Speaker>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let run = PipelineRun::claim(&state, "a importação")?;
+
+    let options = state.settings.lock().unwrap().capture.clone();
+    let speaker = speaker.unwrap_or(Speaker::Customer);
+    // Abrir já aqui: arquivo inexistente ou formato desconhecido voltam como erro do comando
+    let mut decoder = AudioDecoder::open(Path::new(&path))?;
+    let duration_ms = decoder.duration_ms;
+    println!("📥 Importando {}", path);
+
+    let session = Session::new(state.settings.lock().unwrap().transcription.language.clone());
+    let session_dir = state.sessions_dir.join(&session.id);
+    let started_at = session.started_at;
+    let session = Arc::new(Mutex::new(session));
+    *state.session.lock().unwrap() = Some(Arc::clone(&session));
+
+    let transcription_options = state.settings.lock().unwrap().transcription.clone();
+
+    let progress_app = app.clone();
+    let mut processed_chunks = 0;
+    let (queue, delivery) = TranscriptionQueue::start(
+        ChunkPipeline::new(app.clone(), &state, &session, &session_dir),
+        transcription_options,
+        move |chunk| {
+            processed_chunks += 1;
+            let _ = progress_app.emit("import-progress", ImportProgress {
+                processed_chunks,
+                duration_ms,
+                position_ms: chunk.end_ms,
+            });
+        },
+    );
+
+    // Decodificar e segmentar fora do runtime, pacote a pacote. O canal curto faz a
+    // decodificação andar no ritmo da transcrição em vez de acumular o arquivo em chunks.
+    let (chunks_tx, mut chunks) = mpsc::channel::<AudioChunk>(IMPORT_CHUNK_BUFFER);
+    tokio::task::spawn_blocking(move || {
+        let mut splitter = RecordingSplitter::new(speaker, &options);
+        loop {
+            let block = match decoder.next_block() {
+                Ok(Some(block)) => block,
+                Ok(None) => break,
+                // Fica o que já foi decodificado
+                Err(e) => {
+                    eprintln!("❌ {}", e);
+                    break;
+                }
+            };
+            for chunk in splitter.push(&block.samples, block.sample_rate, block.channels) {
+                if chunks_tx.blocking_send(chunk).is_err() {
+                    return;
+                }
+            }
+        }
+        for chunk in splitter.finish() {
+            let _ = chunks_tx.blocking_send(chunk);
+        }
+    });
+
+    tokio::spawn(async move {
+        let mut total_chunks = 0;
+        while let Some(chunk) = chunks.recv().await {
+            total_chunks += 1;
+            // Horário aproximado: início da importação + posição do chunk no arquivo
+            let timestamp = started_at + chunk.start_ms / 1000;
+            queue.push(chunk, timestamp).await;
+        }
+
+        drop(queue);
+        let analyses = delivery.await.unwrap_or_default();
+        for analysis in analyses {
+            let _ = analysis.await;
+        }
+
+        let session = session.lock().unwrap().clone();
+        let saved = std::fs::create_dir_all(&session_dir)
+            .map_err(|e| format!("Erro ao criar diretório da sessão: {}", e))
+            .and_then(|_| session.save(&session_dir));
+        if let Err(e) = saved {
+            eprintln!("⚠️ {}", e);
+        }
+        drop(run);
+        let _ = app.emit("import-finished", session);
+
+        println!("✅ Importação concluída ({} chunks)", total_chunks);
+    });
+
+    Ok(format!("Importando {}", path))
+}
+
+/// Tenta de novo os chunks da sessão atual cuja transcrição falhou (ex.: depois de
+/// uma queda de rede). As transcrições chegam pelos eventos de sempre.
+#[tauri::command]
+pub async fn retry_failed_transcriptions(app: AppHandle, state: State<'_, AppState>) -> Result<String, String> {
+    let _run = PipelineRun::claim(&state, "a nova tentativa de transcrição")?;
+
+    let session = state.current_session().ok_or("Nenhuma sessão")?;
+    let (session_id, started_at) = {
+        let session = session.lock().unwrap();
+        (session.id.clone(), session.started_at)
+    };
+    let session_dir = state.sessions_dir.join(&session_id);
+
+    let dir = session_dir.clone();
+    let chunks = tokio::task::spawn_blocking(move || pending_chunks(&dir))
+        .await
+        .map_err(|e| format!("Erro ao ler chunks pendentes: {}", e))?;
+    let total_chunks = chunks.len();
+
+    let options = state.settings.lock().unwrap().transcription.clone();
+    let (queue, delivery) =
+        TranscriptionQueue::start(ChunkPipeline::new(app, &state, &session, &session_dir), options, |_| {});
+    for chunk in chunks {
+        let timestamp = started_at + chunk.start_ms / 1000;
+        queue.enqueue(chunk, timestamp).await;
+    }
+    drop(queue);
+    for analysis in delivery.await.unwrap_or_default() {
+        let _ = analysis.await;
+    }
+
+    {
+        let mut session = session.lock().unwrap();
+        // Os trechos recuperados entram no lugar deles na conversa
+        session.transcript.sort_by_key(|event| (event.start_ms, event.speaker));
+        if let Err(e) = session.save(&session_dir) {
+            eprintln!("⚠️ {}", e);
+        }
+    }
+
+    let remaining = pending_chunks(&session_dir).len();
+    Ok(format!("{} de {} trechos transcritos", total_chunks - remaining, total_chunks))
+}
+
 #[tauri::command]
 pub async fn stop_realtime_capture(state: State<'_, AppState>) -> Result<String, String> {
     *state.is_realtime.lock().unwrap() = false;
@@ -199,6 +919,51 @@ pub async fn stop_realtime_capture(state: State<'_, AppState>) -> Result<String,
     Ok("Real-time parado".to_string())
 }
 
+/// Para de alimentar a transcrição (ex.: cliente em espera) mantendo a sessão
+#[tauri::command]
+pub async fn pause_realtime_capture(state: State<'_, AppState>) -> Result<String, String> {
+    state.recorder.lock().unwrap().pause_recording()?;
+    
+    if let Some(session) = state.current_session() {
+        session.lock().unwrap().paused = true;
+    }
+    
+    Ok("Real-time pausado".to_string())
+}
+
+#[tauri::command]
+pub async fn resume_realtime_capture(state: State<'_, AppState>) -> Result<String, String> {
+    state.recorder.lock().unwrap().resume_recording()?;
+    
+    if let Some(session) = state.current_session() {
+        session.lock().unwrap().paused = false;
+    }
+    
+    Ok("Real-time retomado".to_string())
+}
+
+/// Transcrição e análise acumuladas da sessão atual (ou da última encerrada)
+#[tauri::command]
+pub async fn get_session(state: State<'_, AppState>) -> Result<Option<Session>, String> {
+    Ok(state.current_session().map(|session| session.lock().unwrap().clone()))
+}
+
+/// Gravações completas de uma sessão (a atual, se nenhuma for indicada) para reprodução
+#[tauri::command]
+pub async fn get_session_recordings(
+    session_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<SessionRecording>, String> {
+    let session_id = match session_id {
+        Some(id) => id,
+        None => state
+            .current_session()
+            .map(|session| session.lock().unwrap().id.clone())
+            .ok_or("Nenhuma sessão iniciada")?,
+    };
+    Ok(list_recordings(&state.sessions_dir.join(session_id)))
+}
+
 #[tauri::command]
 pub async fn analyze_text(
     text: String,
@@ -217,9 +982,20 @@ pub async fn analyze_text(
 }
 
 #[tauri::command]
-pub async fn start_audio_capture(state: State<'_, AppState>) -> Result<String, String> {
-    let recorder = state.recorder.lock().unwrap();
-    recorder.start_recording()?;
+pub async fn start_audio_capture(
+    app: AppHandle,
+    device_name: Option<String>,
+    loopback_device_name: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<String, AudioError> {
+    // Gravação simples: sem transcrição, os chunks só existem como arquivo
+    let mut options = state.settings.lock().unwrap().capture.clone();
+    options.save_chunks = true;
+    let session = Session::new(state.settings.lock().unwrap().transcription.language.clone());
+    let session_dir = state.sessions_dir.join(&session.id);
+    let capture = start_recorder(device_name, loopback_device_name, &options, &session_dir, false, &state)?;
+    *state.session.lock().unwrap() = Some(Arc::new(Mutex::new(session)));
+    forward_meter_events(app, capture.meters);
     Ok("Gravacao iniciada".to_string())
 }
 
@@ -241,11 +1017,156 @@ pub async fn list_audio_devices() -> Result<Vec<AudioDeviceInfo>, String> {
     list_input_devices()
 }
 
+#[tauri::command]
+pub async fn get_selected_audio_device(state: State<'_, AppState>) -> Result<Option<String>, String> {
+    Ok(state.settings.lock().unwrap().input_device.clone())
+}
+
+#[tauri::command]
+pub async fn get_selected_loopback_device(state: State<'_, AppState>) -> Result<Option<String>, String> {
+    Ok(state.settings.lock().unwrap().loopback_device.clone())
+}
+
+/// Escolhe o microfone das próximas capturas (`None` volta para o padrão do sistema)
+#[tauri::command]
+pub async fn select_audio_device(
+    device_name: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<String, AudioError> {
+    if let Some(ref name) = device_name {
+        get_input_device_by_name(name)?;
+    }
+    update_settings(&state, |settings| settings.input_device = device_name);
+    Ok("Dispositivo selecionado".to_string())
+}
+
+/// Escolhe a fonte de loopback do cliente (`None` volta para a detecção automática)
+#[tauri::command]
+pub async fn select_loopback_device(
+    device_name: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<String, AudioError> {
+    if let Some(ref name) = device_name {
+        get_input_device_by_name(name)?;
+    }
+    update_settings(&state, |settings| settings.loopback_device = device_name);
+    Ok("Loopback selecionado".to_string())
+}
+
 #[tauri::command]
 pub async fn transcribe_audio(
     audio_path: String,
     state: State<'_, AppState>,
-) -> Result<TranscriptionResult, String> {
-    let whisper = state.whisper.lock().unwrap();
-    whisper.transcribe_file(&audio_path, "pt")
+) -> Result<Transcript, String> {
+    let started = Instant::now();
+    let (whisper, language) = {
+        let transcription = state.transcription.lock().unwrap();
+        (Arc::clone(&transcription.local), transcription.hints.language.clone())
+    };
+    let response = whisper.lock().unwrap().transcribe_file(&audio_path, language.as_deref())?;
+    Ok(Transcript::from_whisper("local", 0, started, response))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcription::{MockProvider, TranscriptionError, TranscriptionHints, TranscriptionProvider};
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    /// Transcreve como o mock depois do atraso de cada chunk, contando quantas
+    /// transcrições estão no backend ao mesmo tempo
+    struct SlowProvider {
+        delays_ms: HashMap<u64, u64>,
+        running: AtomicUsize,
+        peak: AtomicUsize,
+        /// Início dos chunks, na ordem em que terminaram
+        finished: Mutex<Vec<u64>>,
+    }
+
+    #[async_trait]
+    impl TranscriptionProvider for SlowProvider {
+        fn name(&self) -> &'static str {
+            "mock"
+        }
+
+        async fn transcribe(&self, chunk: &AudioChunk, hints: &TranscriptionHints) -> Result<Transcript, TranscriptionError> {
+            let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+            self.peak.fetch_max(running, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(self.delays_ms[&chunk.start_ms])).await;
+            self.running.fetch_sub(1, Ordering::SeqCst);
+            self.finished.lock().unwrap().push(chunk.start_ms);
+            MockProvider.transcribe(chunk, hints).await
+        }
+    }
+
+    fn chunk(start_ms: u64) -> AudioChunk {
+        AudioChunk {
+            speaker: Speaker::Customer,
+            samples: vec![0.0; 16_000],
+            sample_rate: 16_000,
+            channels: 1,
+            start_ms,
+            end_ms: start_ms + 1_000,
+            path: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn queue_delivers_in_capture_order_within_concurrency() {
+        // O primeiro chunk é o mais lento: os seguintes terminam antes dele
+        let delays_ms = HashMap::from([(0, 120), (1_000, 10), (2_000, 60), (3_000, 0), (4_000, 30), (5_000, 5)]);
+        let starts: Vec<u64> = (0..6).map(|i| i * 1_000).collect();
+        let provider = Arc::new(SlowProvider {
+            delays_ms,
+            running: AtomicUsize::new(0),
+            peak: AtomicUsize::new(0),
+            finished: Mutex::new(Vec::new()),
+        });
+
+        let options = TranscriptionOptions {
+            concurrency: 2,
+            fallback: Vec::new(),
+            // O áudio dos chunks é silêncio: sem filtro, todos rendem texto
+            filter: FilterOptions { enabled: false, ..Default::default() },
+            ..Default::default()
+        };
+        let mut transcribers = Transcribers::new(ProviderKind::Mock, &options, std::env::temp_dir());
+        transcribers.stubs.push((ProviderKind::Mock, Arc::clone(&provider) as Arc<dyn TranscriptionProvider>));
+
+        let app = tauri::test::mock_app();
+        let session = Arc::new(Mutex::new(Session::new(AUTO_LANGUAGE.to_string())));
+        let pipeline = ChunkPipeline {
+            app: app.handle().clone(),
+            transcription: Arc::new(Mutex::new(transcribers)),
+            llm: Arc::new(Mutex::new(None)),
+            session: Arc::clone(&session),
+            session_dir: std::env::temp_dir(),
+            analyses: Arc::new(Semaphore::new(MAX_CONCURRENT_ANALYSES)),
+        };
+
+        let delivered = Arc::new(Mutex::new(Vec::new()));
+        let on_delivered = {
+            let delivered = Arc::clone(&delivered);
+            move |chunk: &AudioChunk| delivered.lock().unwrap().push(chunk.start_ms)
+        };
+        let (queue, delivery) = TranscriptionQueue::start(pipeline, options, on_delivered);
+        for &start_ms in &starts {
+            queue.enqueue(chunk(start_ms), start_ms).await;
+        }
+        drop(queue);
+        for analysis in delivery.await.unwrap() {
+            analysis.await.unwrap();
+        }
+
+        // Os chunks terminaram fora de ordem...
+        assert_ne!(*provider.finished.lock().unwrap(), starts);
+        // ...mas foram publicados na ordem de captura, todos com texto
+        assert_eq!(*delivered.lock().unwrap(), starts);
+        let published: Vec<u64> = session.lock().unwrap().transcript.iter().map(|event| event.start_ms).collect();
+        assert_eq!(published, starts);
+        // Nunca mais que `concurrency` no backend, e a fila de fato usou as duas vagas
+        assert_eq!(provider.peak.load(Ordering::SeqCst), 2);
+    }
 }
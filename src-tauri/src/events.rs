@@ -1,16 +1,85 @@
 use serde::Serialize;
+use crate::audio::{AudioChunk, Speaker};
+use crate::transcript::Transcript;
 
-#[derive(Clone, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct TranscriptionEvent {
-    pub text: String,
     pub timestamp: u64,
-    pub speaker: String,
+    pub speaker: Speaker,
+    /// Início e fim do chunk em ms, relativos ao início da sessão (a ordem da conversa)
+    pub start_ms: u64,
+    pub end_ms: u64,
+    /// Mesmo id dos `transcription-partial` que esta transcrição substitui
+    pub utterance_id: String,
+    /// Texto, segmentos e metadados no nível do próprio evento
+    #[serde(flatten)]
+    pub transcript: Transcript,
 }
 
-#[derive(Clone, Serialize)]
+/// Transcrição provisória de uma fala em andamento. Cada parcial substitui o anterior
+/// de mesmo `utterance_id` e o `transcription-final` substitui todos.
+#[derive(Debug, Clone, Serialize)]
+pub struct PartialTranscriptionEvent {
+    pub utterance_id: String,
+    pub speaker: Speaker,
+    #[serde(flatten)]
+    pub transcript: Transcript,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct AnalysisEvent {
     pub objections: Vec<String>,
     pub important_points: Vec<String>,
     pub sentiment: String,
     pub suggestions: Vec<String>,
 }
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChunkEvent {
+    pub speaker: Speaker,
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub path: Option<String>,
+}
+
+impl From<&AudioChunk> for ChunkEvent {
+    fn from(chunk: &AudioChunk) -> Self {
+        ChunkEvent {
+            speaker: chunk.speaker,
+            start_ms: chunk.start_ms,
+            end_ms: chunk.end_ms,
+            path: chunk.path.as_ref().map(|p| p.to_string_lossy().to_string()),
+        }
+    }
+}
+
+/// Andamento de `import_recording`
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportProgress {
+    pub processed_chunks: usize,
+    /// Duração do arquivo, quando o formato informa (o total de chunks só se sabe no fim)
+    pub duration_ms: Option<u64>,
+    /// Até onde o arquivo já foi transcrito, em ms
+    pub position_ms: u64,
+}
+
+/// Chunk que não foi transcrito nem depois das novas tentativas. O WAV continua em
+/// `chunk.path` até `retry_failed_transcriptions` conseguir transcrevê-lo.
+#[derive(Debug, Clone, Serialize)]
+pub struct TranscriptionErrorEvent {
+    pub chunk: ChunkEvent,
+    /// Ausente quando o backend escolhido nem estava configurado
+    pub provider: Option<String>,
+    pub error: String,
+    pub attempts: u32,
+}
+
+/// O idioma detectado mudou no meio da chamada (só acontece com idioma `auto`)
+#[derive(Debug, Clone, Serialize)]
+pub struct LanguageChange {
+    pub speaker: Speaker,
+    pub from: String,
+    pub to: String,
+    /// Início do chunk em que o novo idioma apareceu, em ms da sessão
+    pub at_ms: u64,
+}
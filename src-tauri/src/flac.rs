@@ -0,0 +1,428 @@
+use std::fs::File;
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::path::Path;
+
+/// Amostras por frame (bloco fixo; só o último pode ser menor)
+const BLOCK_SIZE: usize = 4096;
+const BITS_PER_SAMPLE: u32 = 16;
+/// Maior parâmetro Rice representável com 4 bits (15 é reservado para escape)
+const MAX_RICE_PARAM: u32 = 14;
+const MAX_FIXED_ORDER: usize = 4;
+const MAX_PARTITION_ORDER: u32 = 6;
+/// "fLaC" + cabeçalho do bloco de metadados
+const STREAMINFO_OFFSET: u64 = 8;
+
+/// Gravador FLAC mono de 16 bits, escrito em streaming.
+/// Usa só preditores fixos e resíduo Rice particionado: comprime bem voz sem dependências.
+pub struct FlacWriter {
+    file: BufWriter<File>,
+    sample_rate: u32,
+    block: Vec<i32>,
+    frame_number: u64,
+    total_samples: u64,
+}
+
+impl FlacWriter {
+    pub fn create(path: &Path, sample_rate: u32) -> std::io::Result<Self> {
+        let mut writer = FlacWriter {
+            file: BufWriter::new(File::create(path)?),
+            sample_rate,
+            block: Vec::with_capacity(BLOCK_SIZE),
+            frame_number: 0,
+            total_samples: 0,
+        };
+
+        writer.file.write_all(b"fLaC")?;
+        // Bloco STREAMINFO, o único (e portanto último) bloco de metadados
+        writer.file.write_all(&[0x80, 0x00, 0x00, 34])?;
+        let streaminfo = writer.streaminfo();
+        writer.file.write_all(&streaminfo)?;
+
+        Ok(writer)
+    }
+
+    /// Acrescenta amostras em [-1.0, 1.0]
+    pub fn write_samples(&mut self, samples: &[f32]) -> std::io::Result<()> {
+        for &sample in samples {
+            let value = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i32;
+            self.block.push(value);
+
+            if self.block.len() == BLOCK_SIZE {
+                self.write_frame()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Grava o último frame e atualiza o total de amostras no cabeçalho
+    pub fn finish(mut self) -> std::io::Result<()> {
+        if !self.block.is_empty() {
+            self.write_frame()?;
+        }
+
+        let streaminfo = self.streaminfo();
+        self.file.seek(SeekFrom::Start(STREAMINFO_OFFSET))?;
+        self.file.write_all(&streaminfo)?;
+        self.file.flush()
+    }
+
+    fn streaminfo(&self) -> [u8; 34] {
+        let mut bits = BitWriter::new();
+        bits.write(BLOCK_SIZE as u64, 16);
+        bits.write(BLOCK_SIZE as u64, 16);
+        // Tamanhos mínimo/máximo de frame: 0 = desconhecido
+        bits.write(0, 24);
+        bits.write(0, 24);
+        bits.write(self.sample_rate as u64, 20);
+        bits.write(0, 3); // canais - 1
+        bits.write((BITS_PER_SAMPLE - 1) as u64, 5);
+        bits.write(self.total_samples, 36);
+
+        let mut info = [0u8; 34];
+        // MD5 zerado = não calculado
+        info[..18].copy_from_slice(&bits.into_bytes());
+        info
+    }
+
+    fn write_frame(&mut self) -> std::io::Result<()> {
+        let samples = std::mem::take(&mut self.block);
+        let mut bits = BitWriter::new();
+
+        // Cabeçalho: sync com bloco fixo, tamanho do bloco em 16 bits no fim,
+        // taxa lida do STREAMINFO, mono, 16 bits por amostra
+        bits.write(0xFFF8, 16);
+        bits.write(0b0111, 4);
+        bits.write(0b0000, 4);
+        bits.write(0b0000, 4);
+        bits.write(0b100, 3);
+        bits.write(0, 1);
+        bits.write_utf8(self.frame_number);
+        bits.write((samples.len() - 1) as u64, 16);
+        let header_crc = crc8(bits.bytes());
+        bits.write(header_crc as u64, 8);
+
+        write_fixed_subframe(&mut bits, &samples);
+
+        bits.align();
+        let frame_crc = crc16(bits.bytes());
+        bits.write(frame_crc as u64, 16);
+
+        self.file.write_all(&bits.into_bytes())?;
+        self.frame_number += 1;
+        self.total_samples += samples.len() as u64;
+        self.block = samples;
+        self.block.clear();
+        Ok(())
+    }
+}
+
+/// Escolhe o preditor fixo com menor resíduo e grava o subframe
+fn write_fixed_subframe(bits: &mut BitWriter, samples: &[i32]) {
+    let max_order = MAX_FIXED_ORDER.min(samples.len().saturating_sub(1));
+    let (order, residual) = (0..=max_order)
+        .map(|order| (order, fixed_residual(samples, order)))
+        .min_by_key(|(_, residual)| residual.iter().map(|r| r.unsigned_abs() as u64).sum::<u64>())
+        .unwrap();
+
+    // Tipo FIXED (001xxx) com a ordem, sem bits desperdiçados
+    bits.write(0, 1);
+    bits.write(0b001000 | order as u64, 6);
+    bits.write(0, 1);
+
+    for &warmup in &samples[..order] {
+        bits.write_signed(warmup, BITS_PER_SAMPLE);
+    }
+
+    // Resíduo Rice com parâmetro de 4 bits, na divisão em partições que gasta menos bits
+    let (partition_order, params) = (0..=MAX_PARTITION_ORDER)
+        .filter_map(|p| rice_partitions(&residual, samples.len(), order, p).map(|params| (p, params)))
+        .min_by_key(|(_, params)| params.iter().map(|(_, cost)| cost).sum::<u64>())
+        .unwrap();
+
+    bits.write(0b00, 2);
+    bits.write(partition_order as u64, 4);
+    let mut rest = residual.as_slice();
+    for (i, (param, _)) in params.iter().enumerate() {
+        let mut len = samples.len() >> partition_order;
+        if i == 0 {
+            len -= order;
+        }
+        let (partition, tail) = rest.split_at(len);
+        bits.write(*param as u64, 4);
+        for &r in partition {
+            bits.write_rice(r, *param);
+        }
+        rest = tail;
+    }
+}
+
+/// Parâmetro e custo em bits de cada partição, ou `None` se o bloco não divide nessa ordem
+fn rice_partitions(residual: &[i32], block_size: usize, order: usize, partition_order: u32) -> Option<Vec<(u32, u64)>> {
+    let partitions = 1usize << partition_order;
+    let len = block_size >> partition_order;
+    if !block_size.is_multiple_of(partitions) || len <= order {
+        return None;
+    }
+
+    let mut params = Vec::with_capacity(partitions);
+    let mut start = 0;
+    for i in 0..partitions {
+        let end = if i == 0 { len - order } else { start + len };
+        let folded: Vec<u64> = residual[start..end].iter().map(|&r| zigzag(r) as u64).collect();
+        params.push(best_rice_parameter(&folded));
+        start = end;
+    }
+    Some(params)
+}
+
+fn fixed_residual(samples: &[i32], order: usize) -> Vec<i32> {
+    samples[order..]
+        .iter()
+        .enumerate()
+        .map(|(i, &s)| {
+            let i = i + order;
+            let prediction = match order {
+                0 => 0,
+                1 => samples[i - 1],
+                2 => 2 * samples[i - 1] - samples[i - 2],
+                3 => 3 * samples[i - 1] - 3 * samples[i - 2] + samples[i - 3],
+                _ => 4 * samples[i - 1] - 6 * samples[i - 2] + 4 * samples[i - 3] - samples[i - 4],
+            };
+            s - prediction
+        })
+        .collect()
+}
+
+/// Parâmetro Rice de menor custo e o total de bits que ele gasta (inclui os 4 do parâmetro)
+fn best_rice_parameter(folded: &[u64]) -> (u32, u64) {
+    (0..=MAX_RICE_PARAM)
+        .map(|param| {
+            let cost = folded.iter().map(|&v| (v >> param) + 1 + param as u64).sum::<u64>();
+            (param, cost + 4)
+        })
+        .min_by_key(|(_, cost)| *cost)
+        .unwrap()
+}
+
+fn zigzag(value: i32) -> u32 {
+    ((value << 1) ^ (value >> 31)) as u32
+}
+
+struct BitWriter {
+    bytes: Vec<u8>,
+    acc: u64,
+    len: u32,
+}
+
+impl BitWriter {
+    fn new() -> Self {
+        BitWriter {
+            bytes: Vec::new(),
+            acc: 0,
+            len: 0,
+        }
+    }
+
+    fn write(&mut self, value: u64, bits: u32) {
+        for i in (0..bits).rev() {
+            self.acc = (self.acc << 1) | ((value >> i) & 1);
+            self.len += 1;
+            if self.len == 8 {
+                self.bytes.push(self.acc as u8);
+                self.acc = 0;
+                self.len = 0;
+            }
+        }
+    }
+
+    fn write_signed(&mut self, value: i32, bits: u32) {
+        self.write(value as u64 & ((1u64 << bits) - 1), bits);
+    }
+
+    fn write_unary(&mut self, zeros: u32) {
+        for _ in 0..zeros {
+            self.write(0, 1);
+        }
+        self.write(1, 1);
+    }
+
+    fn write_rice(&mut self, value: i32, param: u32) {
+        let folded = zigzag(value);
+        self.write_unary(folded >> param);
+        self.write((folded & ((1 << param) - 1)) as u64, param);
+    }
+
+    /// Número do frame no formato UTF-8 "estendido" do FLAC
+    fn write_utf8(&mut self, value: u64) {
+        if value < 0x80 {
+            self.write(value, 8);
+            return;
+        }
+
+        let mut continuation = Vec::new();
+        let mut rest = value;
+        let mut first_bits = 6;
+        while rest >= (1 << first_bits) {
+            continuation.push(0x80 | (rest & 0x3F));
+            rest >>= 6;
+            first_bits -= 1;
+        }
+
+        let count = continuation.len() as u32 + 1;
+        let prefix = (0xFF00u64 >> count) & 0xFF;
+        self.write(prefix | rest, 8);
+        for byte in continuation.into_iter().rev() {
+            self.write(byte, 8);
+        }
+    }
+
+    fn align(&mut self) {
+        if self.len > 0 {
+            self.write(0, 8 - self.len);
+        }
+    }
+
+    /// Bytes completos escritos até agora
+    fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    fn into_bytes(mut self) -> Vec<u8> {
+        self.align();
+        self.bytes
+    }
+}
+
+fn crc8(data: &[u8]) -> u8 {
+    let mut crc = 0u8;
+    for &byte in data {
+        crc ^= byte;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 { (crc << 1) ^ 0x07 } else { crc << 1 };
+        }
+    }
+    crc
+}
+
+fn crc16(data: &[u8]) -> u16 {
+    let mut crc = 0u16;
+    for &byte in data {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 { (crc << 1) ^ 0x8005 } else { crc << 1 };
+        }
+    }
+    crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use symphonia::core::audio::SampleBuffer;
+    use symphonia::core::codecs::DecoderOptions;
+    use symphonia::core::errors::Error as SymphoniaError;
+    use symphonia::core::formats::FormatOptions;
+    use symphonia::core::io::MediaSourceStream;
+    use symphonia::core::meta::MetadataOptions;
+    use symphonia::core::probe::Hint;
+
+    const RATE: u32 = 16_000;
+
+    /// Grava `samples` em blocos do tamanho de um callback e devolve o arquivo
+    fn encode(name: &str, samples: &[f32]) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("flac_{}_{}.flac", name, std::process::id()));
+        let mut writer = FlacWriter::create(&path, RATE).unwrap();
+        for block in samples.chunks(480) {
+            writer.write_samples(block).unwrap();
+        }
+        writer.finish().unwrap();
+        path
+    }
+
+    /// Decodifica com o symphonia verificando o arquivo; CRC errado é erro ou frame perdido
+    fn decode(path: &Path) -> (Vec<i16>, Option<u64>) {
+        let stream = MediaSourceStream::new(Box::new(File::open(path).unwrap()), Default::default());
+        let mut hint = Hint::new();
+        hint.with_extension("flac");
+        let mut format = symphonia::default::get_probe()
+            .format(&hint, stream, &FormatOptions::default(), &MetadataOptions::default())
+            .unwrap()
+            .format;
+        let track = format.default_track().unwrap();
+        assert_eq!(track.codec_params.sample_rate, Some(RATE));
+        let n_frames = track.codec_params.n_frames;
+        let mut decoder = symphonia::default::get_codecs()
+            .make(&track.codec_params, &DecoderOptions { verify: true })
+            .unwrap();
+
+        let mut decoded = Vec::new();
+        loop {
+            let packet = match format.next_packet() {
+                Ok(packet) => packet,
+                Err(SymphoniaError::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
+                Err(e) => panic!("erro ao ler o FLAC: {}", e),
+            };
+            let audio = decoder.decode(&packet).unwrap();
+            let mut buffer = SampleBuffer::<i16>::new(audio.capacity() as u64, *audio.spec());
+            buffer.copy_interleaved_ref(audio);
+            decoded.extend_from_slice(buffer.samples());
+        }
+        assert_ne!(decoder.finalize().verify_ok, Some(false));
+        (decoded, n_frames)
+    }
+
+    /// Mesmo arredondamento do gravador
+    fn quantize(samples: &[f32]) -> Vec<i16> {
+        samples.iter().map(|s| (s.clamp(-1.0, 1.0) * i16::MAX as f32) as i16).collect()
+    }
+
+    fn assert_round_trip(name: &str, samples: &[f32]) {
+        let path = encode(name, samples);
+        let (decoded, n_frames) = decode(&path);
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(n_frames, Some(samples.len() as u64), "{}: total no STREAMINFO", name);
+        assert_eq!(decoded.len(), samples.len(), "{}: quantidade de amostras", name);
+        assert!(decoded == quantize(samples), "{}: amostras diferentes", name);
+    }
+
+    /// Voz sintética: dois tons com um pouco de ruído
+    fn speech_like(len: usize) -> Vec<f32> {
+        let mut noise = 0x2545_f491u32;
+        (0..len)
+            .map(|i| {
+                noise ^= noise << 13;
+                noise ^= noise >> 17;
+                noise ^= noise << 5;
+                let t = i as f32 / RATE as f32;
+                0.4 * (2.0 * std::f32::consts::PI * 180.0 * t).sin()
+                    + 0.2 * (2.0 * std::f32::consts::PI * 1_250.0 * t).sin()
+                    + 0.05 * (noise as f32 / u32::MAX as f32 - 0.5)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn round_trips_around_block_boundaries() {
+        for len in [1, BLOCK_SIZE - 1, BLOCK_SIZE, BLOCK_SIZE + 1] {
+            assert_round_trip(&format!("len_{}", len), &speech_like(len));
+        }
+    }
+
+    #[test]
+    fn round_trips_silence() {
+        assert_round_trip("silence", &vec![0.0; BLOCK_SIZE * 3 + 100]);
+    }
+
+    #[test]
+    fn round_trips_full_scale() {
+        // Alternar entre os extremos gera o maior resíduo possível para os preditores
+        let alternating: Vec<f32> = (0..BLOCK_SIZE * 2).map(|i| if i % 2 == 0 { 1.0 } else { -1.0 }).collect();
+        assert_round_trip("alternating", &alternating);
+
+        // Fora de [-1, 1] satura
+        let clipped: Vec<f32> = speech_like(BLOCK_SIZE + 7).iter().map(|s| s * 4.0).collect();
+        assert_round_trip("clipped", &clipped);
+    }
+}
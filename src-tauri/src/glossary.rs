@@ -0,0 +1,267 @@
+use crate::transcript::Transcript;
+
+/// Tamanho máximo do `prompt`: o Whisper só considera ~224 tokens
+const MAX_PROMPT_CHARS: usize = 800;
+/// Semelhança mínima (0-1) para trocar um trecho pelo termo do glossário
+const MIN_SIMILARITY: f32 = 0.8;
+/// Termos mais curtos só são trocados quando a grafia é idêntica: em palavras curtas
+/// uma letra de diferença já é outra palavra ("casa" / "Cassa")
+const MIN_FUZZY_CHARS: usize = 6;
+/// Finais de flexão (plural, gênero): um trecho que só difere do termo neles é outra
+/// forma de uma palavra comum, não o termo mal transcrito ("carros" / "Carro")
+const INFLECTIONS: [&str; 7] = ["", "s", "es", "a", "o", "as", "os"];
+
+/// Quantos termos do início do glossário cabem no `prompt`
+fn prompt_len(glossary: &[String]) -> usize {
+    let mut chars = 0;
+    for (i, term) in glossary.iter().map(|t| t.trim()).enumerate() {
+        if term.is_empty() {
+            continue;
+        }
+        if chars + term.len() + 2 > MAX_PROMPT_CHARS {
+            return i;
+        }
+        chars += if chars == 0 { term.len() } else { term.len() + 2 };
+    }
+    glossary.len()
+}
+
+/// `prompt` do Whisper com os termos do glossário, que puxa o modelo para essas grafias
+pub fn glossary_prompt(glossary: &[String]) -> Option<String> {
+    let prompt = glossary[..prompt_len(glossary)]
+        .iter()
+        .map(|t| t.trim())
+        .filter(|t| !t.is_empty())
+        .collect::<Vec<_>>()
+        .join(", ");
+    (!prompt.is_empty()).then(|| format!("{}.", prompt))
+}
+
+/// Termos que não couberam no `prompt`; nos backends que usam `prompt`, só esses
+/// passam pela correção por semelhança
+pub fn terms_outside_prompt(glossary: &[String]) -> &[String] {
+    &glossary[prompt_len(glossary)..]
+}
+
+/// Troca pela grafia do glossário os trechos parecidos com algum termo
+/// (ex.: "sales force" → "Salesforce"), para backends que não aceitam `prompt` e para os
+/// termos que não couberam nele
+pub fn apply_glossary(transcript: &mut Transcript, glossary: &[String]) {
+    let terms = Terms::new(glossary);
+    if terms.0.is_empty() {
+        return;
+    }
+
+    if transcript.segments.is_empty() {
+        transcript.text = terms.replace(&transcript.text);
+        return;
+    }
+
+    for segment in &mut transcript.segments {
+        segment.text = terms.replace(&segment.text);
+    }
+    transcript.text = transcript
+        .segments
+        .iter()
+        .map(|s| s.text.as_str())
+        .collect::<Vec<_>>()
+        .join(" ");
+}
+
+/// Termos normalizados, dos mais longos para os mais curtos
+struct Terms<'a>(Vec<(&'a str, String)>);
+
+impl<'a> Terms<'a> {
+    fn new(glossary: &'a [String]) -> Self {
+        let mut terms: Vec<(&str, String)> = glossary
+            .iter()
+            .map(|t| t.trim())
+            .filter(|t| !t.is_empty())
+            .map(|t| (t, normalize(t)))
+            .collect();
+        terms.sort_by_key(|(_, normalized)| std::cmp::Reverse(normalized.len()));
+        Terms(terms)
+    }
+
+    fn replace(&self, text: &str) -> String {
+        let words: Vec<&str> = text.split_whitespace().collect();
+        let mut output: Vec<String> = Vec::with_capacity(words.len());
+        let mut i = 0;
+
+        while i < words.len() {
+            // Se o trecho que começa na próxima palavra é mais parecido, esta palavra não faz
+            // parte do termo (ex.: o "o" de "o hub spot")
+            let found = self.best_match(&words, i).filter(|&(_, _, score)| {
+                self.best_match(&words, i + 1).is_none_or(|(_, _, next)| next <= score)
+            });
+
+            match found {
+                // Mesma grafia: a caixa do glossário só vale para siglas e nomes como
+                // "HubSpot"; "Temos" no glossário não põe maiúscula no meio da frase
+                Some((term, 1, _)) if normalize(words[i]) == normalize(term) && !has_own_casing(term) => {
+                    output.push(words[i].to_string());
+                    i += 1;
+                }
+                Some((term, n, _)) => {
+                    // Mantém a pontuação que vinha grudada no fim do trecho
+                    let last = words[i + n - 1];
+                    let trailing: String = last
+                        .chars()
+                        .rev()
+                        .take_while(|c| !c.is_alphanumeric())
+                        .collect::<Vec<_>>()
+                        .into_iter()
+                        .rev()
+                        .collect();
+                    output.push(format!("{}{}", term, trailing));
+                    i += n;
+                }
+                None => {
+                    output.push(words[i].to_string());
+                    i += 1;
+                }
+            }
+        }
+
+        output.join(" ")
+    }
+
+    /// Termo mais parecido com um trecho de 1 a 3 palavras a partir de `start`. O termo pode
+    /// ter saído junto ("salesforce") ou separado ("sales force").
+    fn best_match(&self, words: &[&str], start: usize) -> Option<(&'a str, usize, f32)> {
+        let mut best: Option<(&str, usize, f32)> = None;
+        for (term, normalized) in &self.0 {
+            for n in (1..=3).filter(|n| start + n <= words.len()) {
+                let candidate = normalize(&words[start..start + n].join(""));
+                if let Some(score) = score(&candidate, normalized) {
+                    if best.is_none_or(|(_, _, best_score)| score > best_score) {
+                        best = Some((term, n, score));
+                    }
+                }
+                // Palavras a mais depois do termo (ou de uma flexão dele) não são parte dele:
+                // "contratados e" não é "Contratado" mal escrito
+                if candidate == *normalized || is_inflection(&candidate, normalized) {
+                    break;
+                }
+            }
+        }
+        best
+    }
+}
+
+/// Semelhança do trecho com o termo, se for suficiente para a troca
+fn score(candidate: &str, term: &str) -> Option<f32> {
+    if candidate.is_empty() {
+        return None;
+    }
+    if candidate == term {
+        return Some(1.0);
+    }
+    if term.chars().count() < MIN_FUZZY_CHARS || is_inflection(candidate, term) {
+        return None;
+    }
+    Some(similarity(candidate, term)).filter(|&s| s >= MIN_SIMILARITY)
+}
+
+/// Se os dois só diferem no final de flexão ("fechado" / "fechadas")
+fn is_inflection(a: &str, b: &str) -> bool {
+    let common = a
+        .char_indices()
+        .zip(b.chars())
+        .find(|((_, ca), cb)| ca != cb)
+        .map_or(a.len().min(b.len()), |((index, _), _)| index);
+    INFLECTIONS.contains(&&a[common..]) && INFLECTIONS.contains(&&b[common..])
+}
+
+/// Sigla ou nome com maiúsculas no meio ("SAP", "HubSpot", "iPhone")
+fn has_own_casing(term: &str) -> bool {
+    term.chars().filter(|c| c.is_alphabetic()).skip(1).any(char::is_uppercase)
+}
+
+/// 1 - distância de Levenshtein relativa ao maior texto
+fn similarity(a: &str, b: &str) -> f32 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 1.0;
+    }
+
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+
+    1.0 - previous[b.len()] as f32 / longest as f32
+}
+
+/// Minúsculas, só letras e números (sem espaços: "Sales Force" e "salesforce" empatam)
+fn normalize(text: &str) -> String {
+    text.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn replace(glossary: &[&str], text: &str) -> String {
+        let glossary: Vec<String> = glossary.iter().map(|t| t.to_string()).collect();
+        Terms::new(&glossary).replace(text)
+    }
+
+    #[test]
+    fn leaves_ordinary_words_alone() {
+        assert_eq!(
+            replace(&["Cassa", "Carro", "Temos"], "a casa é boa, temos carros"),
+            "a casa é boa, temos carros"
+        );
+    }
+
+    #[test]
+    fn short_terms_need_exact_spelling() {
+        assert_eq!(replace(&["Asana"], "migramos do asanna"), "migramos do asanna");
+        assert_eq!(replace(&["SAP"], "o sap, o sab"), "o SAP, o sab");
+    }
+
+    #[test]
+    fn skips_inflected_forms() {
+        assert_eq!(replace(&["Contratado"], "os contratados e as contratadas"), "os contratados e as contratadas");
+        assert!(is_inflection("fechado", "fechadas"));
+        assert!(!is_inflection("salesforse", "salesforce"));
+    }
+
+    #[test]
+    fn fixes_misspelled_and_split_terms() {
+        assert_eq!(replace(&["Salesforce"], "integra com o salesforse."), "integra com o Salesforce.");
+        assert_eq!(replace(&["Salesforce"], "usamos sales force hoje"), "usamos Salesforce hoje");
+    }
+
+    #[test]
+    fn keeps_casing_unless_term_has_its_own() {
+        assert_eq!(replace(&["Pipedrive"], "o pipedrive caiu"), "o pipedrive caiu");
+        assert_eq!(replace(&["HubSpot", "SAP"], "trocar hubspot por sap"), "trocar HubSpot por SAP");
+    }
+
+    #[test]
+    fn reports_terms_left_out_of_the_prompt() {
+        let short: Vec<String> = vec!["Salesforce".to_string(), "HubSpot".to_string()];
+        assert!(terms_outside_prompt(&short).is_empty());
+        assert_eq!(glossary_prompt(&short).as_deref(), Some("Salesforce, HubSpot."));
+
+        let long: Vec<String> = (0..100).map(|i| format!("Produto{:03}", i)).collect();
+        let prompt = glossary_prompt(&long).unwrap();
+        let left_out = terms_outside_prompt(&long);
+        assert!(prompt.len() <= MAX_PROMPT_CHARS);
+        assert!(!left_out.is_empty());
+        assert!(!prompt.contains(left_out[0].as_str()));
+        assert!(prompt.contains(long[long.len() - left_out.len() - 1].as_str()));
+    }
+}
@@ -1,39 +1,107 @@
 mod audio;
 mod audio_devices;
+mod audio_import;
 mod commands;
 mod whisper;
-mod groq_whisper;
+mod whisper_api;
 mod events;
+mod flac;
+mod glossary;
 mod llm;
+mod settings;
+mod session;
+mod transcript;
+mod transcript_filter;
+mod transcription;
+#[cfg(feature = "whisper-cpp")]
+mod whisper_cpp;
 
 use commands::{
-    analyze_text, get_recording_path, initialize_groq_whisper, initialize_openai,
-    list_audio_devices, start_audio_capture, start_realtime_capture, stop_audio_capture,
-    stop_realtime_capture, transcribe_audio, AppState,
+    analyze_text, get_glossary, get_recording_path, get_selected_audio_device,
+    get_selected_loopback_device, get_session, get_session_recordings, get_transcription_provider,
+    import_recording, initialize_groq_whisper, initialize_openai, list_audio_devices,
+    pause_realtime_capture, resume_realtime_capture, retry_failed_transcriptions,
+    select_audio_device, select_loopback_device, set_glossary, set_transcription_fallback,
+    set_transcription_language, set_transcription_provider, set_whisper_cpp_model,
+    start_audio_capture, start_realtime_capture, stop_audio_capture, stop_realtime_capture,
+    transcribe_audio, AppState,
 };
 use audio::AudioRecorder;
-use whisper::WhisperService;
-use groq_whisper::GroqWhisperService;
+use std::path::PathBuf;
+use transcription::Transcribers;
+use settings::Settings;
 use std::sync::{Arc, Mutex};
+use tauri::Manager;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
-        .manage(AppState {
-            recorder: Mutex::new(AudioRecorder::new()),
-            whisper: Mutex::new(WhisperService::new()),
-            groq_whisper: Arc::new(Mutex::new(None)),
-            llm: Arc::new(Mutex::new(None)),
-            is_realtime: Arc::new(Mutex::new(false)),
+        .setup(|app| {
+            let settings_path = app.path().app_config_dir()?.join("settings.json");
+            println!("⚙️ Configurações: {:?}", settings_path);
+            let sessions_dir = app.path().app_data_dir()?.join("sessions");
+
+            let settings = Settings::load(&settings_path);
+            let recorder = AudioRecorder::new();
+            // WAVs temporários do backend local fora do diretório dos chunks, que é
+            // limpo a cada sessão enquanto a anterior ainda pode estar transcrevendo
+            let scratch_dir = PathBuf::from(recorder.get_base_dir()).join("scratch");
+            let _ = std::fs::create_dir_all(&scratch_dir);
+            let transcription = Transcribers::new(
+                settings.transcription_provider,
+                &settings.transcription,
+                scratch_dir,
+            );
+            let transcription = Arc::new(Mutex::new(transcription));
+
+            // Modelo whisper.cpp em segundo plano: carregar leva alguns segundos
+            if let Some(model_path) = settings.whisper_cpp_model.clone().filter(|_| cfg!(feature = "whisper-cpp")) {
+                let transcription = Arc::clone(&transcription);
+                std::thread::spawn(move || {
+                    if let Err(e) = transcription::load_whisper_cpp(&transcription, &model_path) {
+                        eprintln!("{}", e);
+                    }
+                });
+            }
+
+            app.manage(AppState {
+                recorder: Mutex::new(recorder),
+                transcription,
+                llm: Arc::new(Mutex::new(None)),
+                is_realtime: Arc::new(Mutex::new(false)),
+                session: Mutex::new(None),
+                running: Arc::new(Mutex::new(None)),
+                settings: Mutex::new(settings),
+                settings_path,
+                sessions_dir,
+            });
+            Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             initialize_groq_whisper,
             initialize_openai,
+            get_transcription_provider,
+            set_transcription_provider,
+            set_transcription_fallback,
+            set_transcription_language,
+            get_glossary,
+            set_glossary,
+            set_whisper_cpp_model,
             list_audio_devices,
+            get_selected_audio_device,
+            select_audio_device,
+            get_selected_loopback_device,
+            select_loopback_device,
             start_audio_capture,
             stop_audio_capture,
             start_realtime_capture,
             stop_realtime_capture,
+            pause_realtime_capture,
+            resume_realtime_capture,
+            get_session,
+            get_session_recordings,
+            import_recording,
+            retry_failed_transcriptions,
             get_recording_path,
             transcribe_audio,
             analyze_text
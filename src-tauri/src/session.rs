@@ -0,0 +1,135 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::audio::{AudioChunk, Speaker};
+use crate::events::{AnalysisEvent, ChunkEvent, TranscriptionEvent};
+use crate::transcript_filter::FilterStats;
+
+/// Estado de uma chamada em tempo real. Sobrevive a pausas e só é trocado
+/// quando uma nova sessão começa.
+#[derive(Debug, Clone, Serialize)]
+pub struct Session {
+    pub id: String,
+    /// Início da sessão (unix, segundos)
+    pub started_at: u64,
+    pub paused: bool,
+    /// Idioma configurado: código ISO 639-1 ou `auto`
+    pub language: String,
+    /// Idioma detectado de cada speaker (vendedor e cliente podem falar línguas diferentes)
+    pub detected_languages: HashMap<Speaker, String>,
+    pub transcript: Vec<TranscriptionEvent>,
+    /// Chunks da sessão; `start_ms`/`end_ms` são offsets na gravação do respectivo speaker
+    pub timeline: Vec<ChunkEvent>,
+    /// Análise acumulada de todos os trechos da sessão
+    pub analysis: AnalysisEvent,
+    /// O que o filtro de alucinações descartou
+    pub filter_stats: FilterStats,
+}
+
+impl Session {
+    pub fn new(language: String) -> Self {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap();
+
+        Session {
+            id: format!("call_{}", now.as_millis()),
+            started_at: now.as_secs(),
+            paused: false,
+            language,
+            detected_languages: HashMap::new(),
+            transcript: Vec::new(),
+            timeline: Vec::new(),
+            analysis: AnalysisEvent::default(),
+            filter_stats: FilterStats::default(),
+        }
+    }
+
+    /// Junta a análise de um novo trecho à da sessão, sem repetir itens
+    pub fn merge_analysis(&mut self, analysis: &AnalysisEvent) {
+        fn merge(target: &mut Vec<String>, items: &[String]) {
+            for item in items {
+                if !target.contains(item) {
+                    target.push(item.clone());
+                }
+            }
+        }
+
+        merge(&mut self.analysis.objections, &analysis.objections);
+        merge(&mut self.analysis.important_points, &analysis.important_points);
+        merge(&mut self.analysis.suggestions, &analysis.suggestions);
+        self.analysis.sentiment = analysis.sentiment.clone();
+    }
+
+    /// Grava `session.json` ao lado das gravações, para ouvir ou transcrever de novo depois
+    pub fn save(&self, dir: &Path) -> Result<(), String> {
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Erro ao serializar sessão: {}", e))?;
+
+        std::fs::write(dir.join("session.json"), content)
+            .map_err(|e| format!("Erro ao salvar sessão: {}", e))
+    }
+}
+
+/// Gravação contínua de um dos lados da chamada
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionRecording {
+    pub speaker: Speaker,
+    pub path: String,
+}
+
+/// Gravações existentes no diretório de uma sessão
+pub fn list_recordings(dir: &Path) -> Vec<SessionRecording> {
+    [Speaker::Seller, Speaker::Customer]
+        .into_iter()
+        .filter_map(|speaker| {
+            let path = dir.join(format!("{}.flac", speaker.as_str()));
+            path.exists().then(|| SessionRecording {
+                speaker,
+                path: path.to_string_lossy().to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Subdiretório da sessão com os chunks que ainda não foram transcritos
+const PENDING_DIR: &str = "pending";
+
+/// Diretório dos chunks ainda não transcritos de uma sessão
+pub fn pending_dir(session_dir: &Path) -> PathBuf {
+    session_dir.join(PENDING_DIR)
+}
+
+/// Onde um chunk fica guardado enquanto não é transcrito
+pub fn pending_chunk_path(session_dir: &Path, chunk: &AudioChunk) -> PathBuf {
+    pending_dir(session_dir).join(chunk.file_name())
+}
+
+/// Chunks cuja transcrição falhou, na ordem em que foram capturados
+pub fn pending_chunks(session_dir: &Path) -> Vec<AudioChunk> {
+    let Ok(entries) = std::fs::read_dir(pending_dir(session_dir)) else {
+        return Vec::new();
+    };
+
+    let mut chunks: Vec<AudioChunk> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            let name = path.file_name()?.to_str()?;
+            let (speaker, start_ms) = name.strip_prefix("chunk_")?.strip_suffix(".wav")?.split_once('_')?;
+            let speaker = match speaker {
+                "seller" => Speaker::Seller,
+                "customer" => Speaker::Customer,
+                _ => return None,
+            };
+
+            AudioChunk::read_wav(&path, speaker, start_ms.parse().ok()?)
+                .map_err(|e| eprintln!("⚠️ {}", e))
+                .ok()
+        })
+        .collect();
+
+    chunks.sort_by_key(|chunk| chunk.start_ms);
+    chunks
+}
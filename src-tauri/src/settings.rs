@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use crate::audio::CaptureOptions;
+use crate::transcription::{ProviderKind, TranscriptionOptions};
+
+/// Preferências do usuário que sobrevivem entre sessões
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Nome do microfone escolhido (como retornado por `list_audio_devices`)
+    pub input_device: Option<String>,
+    /// Fonte de loopback/monitor usada para o áudio do cliente
+    pub loopback_device: Option<String>,
+    pub capture: CaptureOptions,
+    pub transcription_provider: ProviderKind,
+    pub transcription: TranscriptionOptions,
+    /// Modelo GGML do whisper.cpp, carregado ao abrir o app
+    pub whisper_cpp_model: Option<PathBuf>,
+}
+
+impl Settings {
+    /// Carrega do disco; arquivo ausente ou inválido volta para o padrão
+    pub fn load(path: &Path) -> Self {
+        match std::fs::read_to_string(path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                eprintln!("⚠️ Configurações inválidas em {:?}: {}", path, e);
+                Settings::default()
+            }),
+            Err(_) => Settings::default(),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Erro ao criar diretório de configuração: {}", e))?;
+        }
+
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Erro ao serializar configurações: {}", e))?;
+
+        std::fs::write(path, content)
+            .map_err(|e| format!("Erro ao salvar configurações: {}", e))
+    }
+}
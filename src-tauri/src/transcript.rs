@@ -0,0 +1,344 @@
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+
+/// Palavra com tempos, em segundos relativos ao áudio enviado
+#[derive(Debug, Clone, Deserialize)]
+pub struct WhisperWord {
+    pub word: String,
+    pub start: f64,
+    pub end: f64,
+    /// Só os modelos locais informam; as APIs não
+    #[serde(default)]
+    pub probability: Option<f64>,
+}
+
+/// Segmento como os backends Whisper devolvem: segundos relativos ao áudio enviado
+#[derive(Debug, Clone, Deserialize)]
+pub struct WhisperSegment {
+    pub start: f64,
+    pub end: f64,
+    pub text: String,
+    #[serde(default)]
+    pub avg_logprob: Option<f64>,
+    /// Probabilidade de o trecho não ter fala
+    #[serde(default)]
+    pub no_speech_prob: Option<f64>,
+    /// Palavras do segmento (modelos locais)
+    #[serde(default)]
+    pub words: Vec<WhisperWord>,
+}
+
+/// Resposta comum dos backends (`verbose_json` das APIs e saída dos scripts locais)
+#[derive(Debug, Clone, Deserialize)]
+pub struct WhisperResponse {
+    #[serde(alias = "full_text")]
+    pub text: String,
+    pub language: Option<String>,
+    /// Duração do áudio em segundos
+    pub duration: Option<f64>,
+    #[serde(default)]
+    pub segments: Vec<WhisperSegment>,
+    /// Palavras do áudio inteiro, fora dos segmentos (`timestamp_granularities` das APIs)
+    #[serde(default)]
+    pub words: Vec<WhisperWord>,
+}
+
+/// Palavra transcrita, com tempos relativos ao início da sessão
+#[derive(Debug, Clone, Serialize)]
+pub struct TranscriptWord {
+    pub word: String,
+    pub start_ms: u64,
+    pub end_ms: u64,
+    /// Probabilidade (0-1) da palavra; ausente nas APIs, que não informam
+    pub confidence: Option<f32>,
+}
+
+/// Trecho transcrito, com tempos relativos ao início da sessão
+#[derive(Debug, Clone, Serialize)]
+pub struct TranscriptSegment {
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub text: String,
+    /// Probabilidade média dos tokens (0-1), quando o backend informa
+    pub confidence: Option<f32>,
+    /// Probabilidade de o trecho ser só silêncio/ruído, quando o backend informa
+    pub no_speech_prob: Option<f32>,
+    /// Vazio quando o backend não informa tempos por palavra
+    pub words: Vec<TranscriptWord>,
+    /// Idioma detectado no áudio deste segmento (código ISO 639-1)
+    pub language: Option<String>,
+}
+
+/// Modelo único de transcrição: o que todo backend produz e todo evento/comando devolve
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Transcript {
+    pub text: String,
+    pub language: Option<String>,
+    pub duration_ms: Option<u64>,
+    pub segments: Vec<TranscriptSegment>,
+    /// Média da confiança dos segmentos
+    pub confidence: Option<f32>,
+    /// Backend que transcreveu (`groq`, `openai`, `local`, `whispercpp`, `mock`); vazio, e
+    /// fora do JSON, no `transcription-final` de um chunk descartado
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub provider: String,
+    /// Tempo gasto pelo backend, em ms
+    pub latency_ms: u64,
+}
+
+impl Transcript {
+    /// Converte a resposta de um backend. `offset_ms` é onde o áudio enviado começa na
+    /// sessão; `started` é o momento em que a chamada ao backend começou.
+    pub fn from_whisper(provider: &str, offset_ms: u64, started: Instant, response: WhisperResponse) -> Self {
+        let to_session_ms = |seconds: f64| offset_ms + (seconds.max(0.0) * 1000.0).round() as u64;
+        // Whisper detecta um idioma por áudio enviado, que vale para todos os segmentos dele
+        let language = response.language.as_deref().map(language_code);
+
+        let to_word = |w: WhisperWord| TranscriptWord {
+            word: w.word.trim().to_string(),
+            start_ms: to_session_ms(w.start),
+            end_ms: to_session_ms(w.end),
+            confidence: w.probability.map(|p| p.clamp(0.0, 1.0) as f32),
+        };
+
+        // Palavras soltas das APIs vão para o segmento em que o meio delas cai
+        let mut loose_words = response.words.into_iter().peekable();
+        let segment_count = response.segments.len();
+
+        let segments: Vec<TranscriptSegment> = response
+            .segments
+            .into_iter()
+            .enumerate()
+            .map(|(index, s)| {
+                let mut words: Vec<TranscriptWord> = s.words.into_iter().map(to_word).collect();
+                let is_last = index + 1 == segment_count;
+                while let Some(word) =
+                    loose_words.next_if(|w| is_last || (w.start + w.end) / 2.0 < s.end)
+                {
+                    words.push(to_word(word));
+                }
+                words.retain(|w| !w.word.is_empty());
+
+                TranscriptSegment {
+                    start_ms: to_session_ms(s.start),
+                    end_ms: to_session_ms(s.end),
+                    text: s.text.trim().to_string(),
+                    confidence: logprob_confidence(s.avg_logprob),
+                    no_speech_prob: s.no_speech_prob.map(|p| p as f32),
+                    words,
+                    language: language.clone(),
+                }
+            })
+            .collect();
+
+        let scores: Vec<f32> = segments.iter().filter_map(|s| s.confidence).collect();
+        let confidence = (!scores.is_empty()).then(|| scores.iter().sum::<f32>() / scores.len() as f32);
+
+        Transcript {
+            text: response.text.trim().to_string(),
+            language,
+            duration_ms: response.duration.map(|d| (d * 1000.0).round() as u64),
+            segments,
+            confidence,
+            provider: provider.to_string(),
+            latency_ms: started.elapsed().as_millis() as u64,
+        }
+    }
+}
+
+/// Quantas palavras do fim do trecho anterior podem se repetir no início do seguinte
+const MAX_STITCH_WORDS: usize = 8;
+/// Repetição mínima para contar como sobreposição: uma palavra só se repete na fala
+/// de verdade ("...acho que sim." / "Sim, vamos...")
+const MIN_STITCH_WORDS: usize = 2;
+
+impl Transcript {
+    /// Fim do último segmento, em ms da sessão
+    fn end_ms(&self) -> Option<u64> {
+        self.segments.iter().map(|s| s.end_ms).max()
+    }
+
+    /// Remove do início deste trecho o que repete o fim de `previous`, o trecho anterior
+    /// do mesmo speaker cujo áudio (terminado em `previous_audio_end_ms`) se sobrepõe a
+    /// este. Palavras e segmentos inteiros que caem antes do fim de `previous` saem pelos
+    /// tempos; o que sobra do limite sai comparando o texto.
+    pub fn stitch_after(&mut self, previous: &Transcript, previous_audio_end_ms: u64) {
+        // Tempos do Whisper podem passar do fim do áudio enviado
+        let previous_end = previous
+            .end_ms()
+            .map_or(previous_audio_end_ms, |end| end.min(previous_audio_end_ms));
+
+        for segment in &mut self.segments {
+            if segment.words.is_empty() {
+                continue;
+            }
+            let before = segment.words.len();
+            segment.words.retain(|w| (w.start_ms + w.end_ms) / 2 >= previous_end);
+            if segment.words.len() != before {
+                segment.text = segment.words.iter().map(|w| w.word.trim()).collect::<Vec<_>>().join(" ");
+                segment.start_ms = segment.words.first().map_or(segment.end_ms, |w| w.start_ms);
+            }
+        }
+        self.segments
+            .retain(|s| !s.text.is_empty() && (!s.words.is_empty() || s.end_ms > previous_end));
+
+        match self.segments.first_mut() {
+            Some(first) if first.words.is_empty() => {
+                first.text = drop_repeated_prefix(&previous.text, &first.text);
+                if first.text.is_empty() {
+                    self.segments.remove(0);
+                }
+            }
+            Some(_) => {}
+            // Backend sem segmentos: só dá para comparar o texto
+            None => {
+                self.text = drop_repeated_prefix(&previous.text, &self.text);
+                return;
+            }
+        }
+
+        self.text = self.segments.iter().map(|s| s.text.as_str()).collect::<Vec<_>>().join(" ");
+    }
+}
+
+/// `text` sem as primeiras palavras, se elas forem exatamente as últimas de `previous`
+/// (ignorando maiúsculas e pontuação). Fica com a repetição mais longa encontrada, de
+/// pelo menos `MIN_STITCH_WORDS` palavras.
+fn drop_repeated_prefix(previous: &str, text: &str) -> String {
+    fn normalize(word: &str) -> String {
+        word.chars().filter(|c| c.is_alphanumeric()).flat_map(char::to_lowercase).collect()
+    }
+
+    let tail: Vec<String> = previous.split_whitespace().map(normalize).collect();
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let head: Vec<String> = words.iter().map(|w| normalize(w)).collect();
+
+    let max = MAX_STITCH_WORDS.min(tail.len()).min(head.len());
+    let repeated = (MIN_STITCH_WORDS..=max)
+        .rev()
+        .find(|&n| tail[tail.len() - n..] == head[..n])
+        .unwrap_or(0);
+
+    words[repeated..].join(" ")
+}
+
+/// Código ISO 639-1 do idioma. As APIs respondem o nome em inglês (`portuguese`);
+/// os modelos locais, o código.
+fn language_code(language: &str) -> String {
+    let language = language.trim().to_lowercase();
+    let code = match language.as_str() {
+        "portuguese" => "pt",
+        "english" => "en",
+        "spanish" => "es",
+        "french" => "fr",
+        "german" => "de",
+        "italian" => "it",
+        "dutch" => "nl",
+        "russian" => "ru",
+        "japanese" => "ja",
+        "chinese" => "zh",
+        "korean" => "ko",
+        "arabic" => "ar",
+        "hindi" => "hi",
+        "polish" => "pl",
+        "turkish" => "tr",
+        "ukrainian" => "uk",
+        "catalan" => "ca",
+        "galician" => "gl",
+        _ => return language,
+    };
+    code.to_string()
+}
+
+/// `avg_logprob` do Whisper convertido em probabilidade
+fn logprob_confidence(avg_logprob: Option<f64>) -> Option<f32> {
+    avg_logprob.map(|logprob| logprob.exp().clamp(0.0, 1.0) as f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text_only(text: &str) -> Transcript {
+        Transcript {
+            text: text.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Segmento com uma palavra a cada 400 ms a partir de `start_ms`
+    fn timed(text: &str, start_ms: u64) -> Transcript {
+        let words: Vec<TranscriptWord> = text
+            .split_whitespace()
+            .enumerate()
+            .map(|(i, word)| TranscriptWord {
+                word: word.to_string(),
+                start_ms: start_ms + i as u64 * 400,
+                end_ms: start_ms + i as u64 * 400 + 350,
+                confidence: None,
+            })
+            .collect();
+        let segment = TranscriptSegment {
+            start_ms,
+            end_ms: words.last().map_or(start_ms, |w| w.end_ms),
+            text: text.to_string(),
+            confidence: None,
+            no_speech_prob: None,
+            words,
+            language: None,
+        };
+        Transcript {
+            text: text.to_string(),
+            segments: vec![segment],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn drops_exact_repeated_prefix() {
+        let previous = text_only("vamos fechar o contrato hoje");
+        let mut transcript = text_only("o contrato hoje mesmo então");
+        transcript.stitch_after(&previous, 5_000);
+        assert_eq!(transcript.text, "mesmo então");
+    }
+
+    #[test]
+    fn drops_only_the_overlapping_part() {
+        let previous = text_only("a proposta cobre o suporte anual");
+        let mut transcript = text_only("suporte anual e a instalação");
+        transcript.stitch_after(&previous, 5_000);
+        assert_eq!(transcript.text, "e a instalação");
+
+        // Com tempos por palavra: saem só as que caem antes do fim do anterior
+        let previous = timed("a proposta cobre o suporte anual", 0);
+        let mut transcript = timed("suporte anual e a instalação", 1_600);
+        transcript.stitch_after(&previous, 2_350);
+        assert_eq!(transcript.text, "e a instalação");
+        assert_eq!(transcript.segments[0].start_ms, 2_400);
+    }
+
+    #[test]
+    fn keeps_a_single_repeated_word() {
+        let previous = text_only("eu acho que sim");
+        let mut transcript = text_only("sim, vamos fechar");
+        transcript.stitch_after(&previous, 5_000);
+        assert_eq!(transcript.text, "sim, vamos fechar");
+    }
+
+    #[test]
+    fn ignores_punctuation_and_case() {
+        let previous = text_only("Vamos fechar o Contrato.");
+        let mut transcript = text_only("o contrato, hoje mesmo");
+        transcript.stitch_after(&previous, 5_000);
+        assert_eq!(transcript.text, "hoje mesmo");
+    }
+
+    #[test]
+    fn keeps_text_without_previous_chunk() {
+        assert_eq!(drop_repeated_prefix("", "olá, tudo bem"), "olá, tudo bem");
+
+        let mut transcript = timed("olá tudo bem", 0);
+        transcript.stitch_after(&Transcript::default(), 0);
+        assert_eq!(transcript.text, "olá tudo bem");
+    }
+}
@@ -0,0 +1,275 @@
+use serde::{Deserialize, Serialize};
+use crate::audio::{AudioChunk, VAD_FRAME_MS};
+use crate::transcript::Transcript;
+
+/// Critérios para descartar alucinações do Whisper ("Obrigado por assistir",
+/// "Legendas pela comunidade Amara.org"...) antes de publicar a transcrição
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FilterOptions {
+    pub enabled: bool,
+    /// Chunks com RMS abaixo disso não têm fala que justifique texto. Medido só nos
+    /// frames com voz, para que o padding em silêncio não derrube falas curtas e baixas.
+    pub min_rms: f32,
+    /// Um segmento é descartado quando passa desse `no_speech_prob`...
+    pub max_no_speech_prob: f32,
+    /// ...e ao mesmo tempo fica abaixo desse `avg_logprob` (mesma regra do Whisper)
+    pub min_avg_logprob: f32,
+    /// Frases que o modelo inventa; comparadas sem maiúsculas nem pontuação
+    pub blocklist: Vec<String>,
+}
+
+impl Default for FilterOptions {
+    fn default() -> Self {
+        FilterOptions {
+            enabled: true,
+            min_rms: 0.005,
+            max_no_speech_prob: 0.6,
+            min_avg_logprob: -1.0,
+            blocklist: [
+                "Legendas pela comunidade Amara.org",
+                "Obrigado por assistir",
+                "Obrigada por assistir",
+                "Inscreva-se no canal",
+                "Subtítulos realizados por la comunidad de Amara.org",
+                "Thanks for watching",
+                "Thank you for watching",
+            ]
+            .into_iter()
+            .map(str::to_string)
+            .collect(),
+        }
+    }
+}
+
+/// Quanto o filtro descartou na sessão
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FilterStats {
+    /// Chunks inteiros descartados por energia baixa
+    pub low_energy_chunks: u32,
+    /// Segmentos com cara de silêncio (`no_speech_prob` alto e `avg_logprob` baixo)
+    pub no_speech_segments: u32,
+    /// Segmentos com uma frase da lista de bloqueio
+    pub blocklisted_segments: u32,
+}
+
+impl FilterOptions {
+    /// Remove da transcrição o que parece alucinação e conta o que saiu.
+    /// Devolve `false` quando não sobrou nada para publicar.
+    pub fn apply(&self, chunk: &AudioChunk, transcript: &mut Transcript, stats: &mut FilterStats) -> bool {
+        if !self.enabled {
+            return true;
+        }
+
+        if voiced_rms(chunk) < self.min_rms {
+            println!("🚫 Transcrição descartada, chunk sem energia: {}", transcript.text);
+            stats.low_energy_chunks += 1;
+            return false;
+        }
+
+        let blocklist: Vec<String> = self
+            .blocklist
+            .iter()
+            .map(|phrase| normalize(phrase))
+            .filter(|phrase| !phrase.is_empty())
+            .collect();
+        let is_blocklisted = |text: &str| {
+            let text = normalize(text);
+            blocklist.iter().any(|phrase| text.contains(phrase.as_str()))
+        };
+
+        // Sem segmentos, só o texto inteiro pode ser comparado
+        if transcript.segments.is_empty() {
+            if is_blocklisted(&transcript.text) {
+                println!("🚫 Transcrição descartada, frase bloqueada: {}", transcript.text);
+                stats.blocklisted_segments += 1;
+                return false;
+            }
+            return true;
+        }
+
+        let min_confidence = self.min_avg_logprob.exp();
+        let before = transcript.segments.len();
+        transcript.segments.retain(|segment| {
+            let silent = segment.no_speech_prob.is_some_and(|p| p > self.max_no_speech_prob)
+                && segment.confidence.is_some_and(|c| c < min_confidence);
+            if silent {
+                println!("🚫 Segmento descartado, sem fala: {}", segment.text);
+                stats.no_speech_segments += 1;
+                return false;
+            }
+            if is_blocklisted(&segment.text) {
+                println!("🚫 Segmento descartado, frase bloqueada: {}", segment.text);
+                stats.blocklisted_segments += 1;
+                return false;
+            }
+            true
+        });
+
+        if transcript.segments.len() != before {
+            transcript.text = transcript
+                .segments
+                .iter()
+                .map(|s| s.text.as_str())
+                .collect::<Vec<_>>()
+                .join(" ");
+        }
+        !transcript.text.trim().is_empty()
+    }
+}
+
+/// Frames com voz são os que chegam a essa fração do frame mais forte do chunk (-20 dB)
+const VOICED_FRAME_RATIO: f32 = 0.1;
+
+/// RMS dos frames com voz do chunk, sem o silêncio do padding e da sobreposição
+fn voiced_rms(chunk: &AudioChunk) -> f32 {
+    let frame_len = (chunk.sample_rate as u64 * VAD_FRAME_MS / 1000) as usize * chunk.channels.max(1) as usize;
+    let energies: Vec<f32> = chunk
+        .samples
+        .chunks(frame_len.max(1))
+        .map(|frame| frame.iter().map(|s| s * s).sum::<f32>() / frame.len() as f32)
+        .collect();
+    let peak = energies.iter().copied().fold(0.0, f32::max);
+    // Energia é RMS ao quadrado: a razão também vai ao quadrado
+    let voiced: Vec<f32> = energies
+        .into_iter()
+        .filter(|&energy| peak > 0.0 && energy >= peak * VOICED_FRAME_RATIO * VOICED_FRAME_RATIO)
+        .collect();
+    if voiced.is_empty() {
+        return 0.0;
+    }
+    (voiced.iter().sum::<f32>() / voiced.len() as f32).sqrt()
+}
+
+/// Minúsculas, só letras, números e um espaço entre palavras
+fn normalize(text: &str) -> String {
+    text.split_whitespace()
+        .map(|word| {
+            word.chars()
+                .filter(|c| c.is_alphanumeric())
+                .flat_map(char::to_lowercase)
+                .collect::<String>()
+        })
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::Speaker;
+    use crate::transcript::TranscriptSegment;
+
+    const RATE: u32 = 16_000;
+
+    /// `speech_ms` de tom com amplitude `amplitude`, no meio de `total_ms` de silêncio
+    fn chunk(amplitude: f32, speech_ms: u64, total_ms: u64) -> AudioChunk {
+        let total = (RATE as u64 * total_ms / 1000) as usize;
+        let speech = (RATE as u64 * speech_ms / 1000) as usize;
+        let start = (total - speech) / 2;
+        let samples = (0..total)
+            .map(|i| {
+                if (start..start + speech).contains(&i) {
+                    amplitude * (2.0 * std::f32::consts::PI * 220.0 * i as f32 / RATE as f32).sin()
+                } else {
+                    0.0
+                }
+            })
+            .collect();
+        AudioChunk {
+            speaker: Speaker::Customer,
+            samples,
+            sample_rate: RATE,
+            channels: 1,
+            start_ms: 0,
+            end_ms: total_ms,
+            path: None,
+        }
+    }
+
+    fn segment(text: &str, no_speech_prob: f32, avg_logprob: f32) -> TranscriptSegment {
+        TranscriptSegment {
+            start_ms: 0,
+            end_ms: 1_000,
+            text: text.to_string(),
+            confidence: Some(avg_logprob.exp()),
+            no_speech_prob: Some(no_speech_prob),
+            words: Vec::new(),
+            language: None,
+        }
+    }
+
+    fn transcript(segments: Vec<TranscriptSegment>) -> Transcript {
+        Transcript {
+            text: segments.iter().map(|s| s.text.as_str()).collect::<Vec<_>>().join(" "),
+            segments,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn drops_blocklisted_phrases() {
+        let options = FilterOptions::default();
+        let mut stats = FilterStats::default();
+        let loud = chunk(0.3, 1_000, 2_000);
+
+        let mut with_segments = transcript(vec![
+            segment("Pode mandar a proposta.", 0.1, -0.3),
+            segment("Obrigado por assistir!", 0.1, -0.3),
+        ]);
+        assert!(options.apply(&loud, &mut with_segments, &mut stats));
+        assert_eq!(with_segments.text, "Pode mandar a proposta.");
+
+        // Sem segmentos, a comparação é no texto inteiro
+        let mut text_only = Transcript {
+            text: "Legendas pela comunidade Amara.org".to_string(),
+            ..Default::default()
+        };
+        assert!(!options.apply(&loud, &mut text_only, &mut stats));
+        assert_eq!(stats.blocklisted_segments, 2);
+    }
+
+    #[test]
+    fn drops_segments_without_speech() {
+        let options = FilterOptions::default();
+        let mut stats = FilterStats::default();
+        let loud = chunk(0.3, 1_000, 2_000);
+
+        let mut silent = transcript(vec![segment("Tchau.", 0.9, -1.5), segment("Fechado então.", 0.1, -0.3)]);
+        assert!(options.apply(&loud, &mut silent, &mut stats));
+        assert_eq!(silent.text, "Fechado então.");
+        assert_eq!(stats.no_speech_segments, 1);
+
+        // Só um dos critérios não basta, como no próprio Whisper
+        let mut unsure = transcript(vec![segment("Tchau.", 0.9, -0.3), segment("Até amanhã.", 0.1, -1.5)]);
+        assert!(options.apply(&loud, &mut unsure, &mut stats));
+        assert_eq!(unsure.segments.len(), 2);
+        assert_eq!(stats.no_speech_segments, 1);
+    }
+
+    #[test]
+    fn drops_chunks_without_energy() {
+        let options = FilterOptions::default();
+        let mut stats = FilterStats::default();
+
+        let mut hiss = transcript(vec![segment("Obrigado.", 0.1, -0.3)]);
+        assert!(!options.apply(&chunk(0.002, 2_000, 2_000), &mut hiss, &mut stats));
+        assert_eq!(stats.low_energy_chunks, 1);
+    }
+
+    #[test]
+    fn measures_energy_over_voiced_frames() {
+        let options = FilterOptions::default();
+        let mut stats = FilterStats::default();
+
+        // 300 ms de fala baixa em 3 s de chunk: no buffer inteiro o RMS fica abaixo do mínimo
+        let quiet = chunk(0.02, 300, 3_000);
+        let whole_rms = (quiet.samples.iter().map(|s| s * s).sum::<f32>() / quiet.samples.len() as f32).sqrt();
+        assert!(whole_rms < options.min_rms);
+
+        let mut short = transcript(vec![segment("Sim.", 0.1, -0.3)]);
+        assert!(options.apply(&quiet, &mut short, &mut stats));
+        assert_eq!(stats.low_energy_chunks, 0);
+    }
+}
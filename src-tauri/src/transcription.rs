@@ -0,0 +1,672 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::{Duration, Instant};
+use crate::audio::AudioChunk;
+use crate::whisper_api::WhisperApiService;
+use crate::transcript::{Transcript, WhisperResponse, WhisperSegment, WhisperWord};
+use crate::transcript_filter::FilterOptions;
+use crate::glossary::{apply_glossary, glossary_prompt, terms_outside_prompt};
+use crate::whisper::WhisperService;
+#[cfg(feature = "whisper-cpp")]
+use crate::whisper_cpp::WhisperCppService;
+
+/// Backend de transcrição escolhido pelo usuário
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProviderKind {
+    /// API do Groq (padrão, mais rápida)
+    #[default]
+    Groq,
+    /// API de Whisper da OpenAI
+    OpenAi,
+    /// faster-whisper local, sem rede
+    Local,
+    /// whisper.cpp embutido (feature `whisper-cpp`), sem Python nem rede
+    WhisperCpp,
+    /// Texto fixo, para desenvolver sem chave nem modelo
+    Mock,
+}
+
+/// Valor de `language` que deixa o modelo detectar o idioma
+pub const AUTO_LANGUAGE: &str = "auto";
+
+/// Opções de transcrição configuráveis pelo usuário
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TranscriptionOptions {
+    /// Idioma da chamada: código ISO 639-1 (`pt`, `en`, `es`...) ou `auto`
+    pub language: String,
+    /// Nomes de produtos, concorrentes e jargões, na grafia correta
+    pub glossary: Vec<String>,
+    /// Quantos chunks podem estar no backend ao mesmo tempo
+    pub concurrency: usize,
+    /// Backends tentados, em ordem, quando o principal falha (ex.: `["openai", "local"]`)
+    pub fallback: Vec<ProviderKind>,
+    /// Por quanto tempo o principal é deixado por último depois de uma falha
+    pub fallback_cooldown_secs: u64,
+    pub filter: FilterOptions,
+}
+
+impl Default for TranscriptionOptions {
+    fn default() -> Self {
+        TranscriptionOptions {
+            language: "pt".to_string(),
+            glossary: Vec::new(),
+            concurrency: 3,
+            fallback: Vec::new(),
+            fallback_cooldown_secs: 60,
+            filter: FilterOptions::default(),
+        }
+    }
+}
+
+/// O que os backends recebem além do áudio
+#[derive(Debug, Clone, Default)]
+pub struct TranscriptionHints {
+    /// `None` deixa o modelo detectar o idioma
+    pub language: Option<String>,
+    pub glossary: Vec<String>,
+}
+
+impl TranscriptionHints {
+    pub fn new(options: &TranscriptionOptions) -> Self {
+        let mut hints = TranscriptionHints {
+            language: None,
+            glossary: options.glossary.clone(),
+        };
+        hints.set_language(&options.language);
+        hints
+    }
+
+    pub fn set_language(&mut self, language: &str) {
+        let language = language.trim().to_lowercase();
+        self.language = (!language.is_empty() && language != AUTO_LANGUAGE).then_some(language);
+    }
+
+    /// `prompt` do Whisper com o glossário
+    pub fn prompt(&self) -> Option<String> {
+        glossary_prompt(&self.glossary)
+    }
+}
+
+/// Falha de um backend, separando o que vale tentar de novo
+#[derive(Debug, Clone)]
+pub enum TranscriptionError {
+    /// Timeout, rede, 429 ou 5xx; `retry_after` vem do cabeçalho `retry-after`
+    Transient {
+        message: String,
+        retry_after: Option<Duration>,
+    },
+    /// Chave inválida, áudio recusado, backend não configurado...
+    Permanent(String),
+}
+
+impl TranscriptionError {
+    pub fn transient(message: String) -> Self {
+        TranscriptionError::Transient { message, retry_after: None }
+    }
+}
+
+impl std::fmt::Display for TranscriptionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TranscriptionError::Transient { message, .. } => write!(f, "{}", message),
+            TranscriptionError::Permanent(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl From<String> for TranscriptionError {
+    fn from(message: String) -> Self {
+        TranscriptionError::Permanent(message)
+    }
+}
+
+/// Tentativas por chunk, contando a primeira
+const MAX_ATTEMPTS: u32 = 4;
+/// Tentativas antes de passar o chunk ao próximo backend da cadeia
+const FAILOVER_ATTEMPTS: u32 = 2;
+/// Espera antes da segunda tentativa; dobra a cada nova falha
+const BASE_BACKOFF: Duration = Duration::from_millis(500);
+/// Teto da espera, inclusive quando o `retry-after` pede mais
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+#[async_trait]
+pub trait TranscriptionProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Se o backend usa o `prompt` com o glossário; os que não usam recebem a correção
+    /// por semelhança depois da transcrição (os que usam, só com os termos que não
+    /// couberam no `prompt`)
+    fn supports_prompt(&self) -> bool {
+        false
+    }
+
+    /// Transcreve o chunk, com tempos relativos ao início da sessão
+    async fn transcribe(&self, chunk: &AudioChunk, hints: &TranscriptionHints) -> Result<Transcript, TranscriptionError>;
+}
+
+/// Transcreve repetindo falhas transitórias com backoff exponencial e jitter.
+/// Devolve o erro da última tentativa e quantas foram feitas.
+async fn transcribe_with_retry(
+    provider: &dyn TranscriptionProvider,
+    chunk: &AudioChunk,
+    hints: &TranscriptionHints,
+    max_attempts: u32,
+) -> Result<Transcript, (TranscriptionError, u32)> {
+    let mut attempt = 1;
+    loop {
+        let error = match provider.transcribe(chunk, hints).await {
+            Ok(transcript) => return Ok(transcript),
+            Err(error) => error,
+        };
+
+        let retry_after = match &error {
+            TranscriptionError::Transient { retry_after, .. } if attempt < max_attempts => *retry_after,
+            _ => return Err((error, attempt)),
+        };
+
+        // Jitter "cheio": espalha as novas tentativas de vários chunks que falharam juntos
+        let backoff = BASE_BACKOFF * 2u32.pow(attempt - 1);
+        let delay = retry_after.unwrap_or_else(|| jitter(backoff)).min(MAX_BACKOFF);
+        println!(
+            "⏳ {} falhou ({}), tentativa {} de {} em {} ms",
+            provider.name(),
+            error,
+            attempt + 1,
+            max_attempts,
+            delay.as_millis()
+        );
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+/// Chunk que nenhum backend da cadeia conseguiu transcrever
+pub struct TranscriptionFailure {
+    /// Último backend tentado; ausente quando nenhum estava configurado
+    pub provider: Option<&'static str>,
+    pub error: String,
+    /// Tentativas somadas de todos os backends
+    pub attempts: u32,
+}
+
+/// Transcreve com o principal e, se ele falhar, com cada backend do fallback, na ordem.
+/// Uma falha do principal o deixa por último durante o cool-down; depois disso ele
+/// volta a ser tentado primeiro.
+pub async fn transcribe_with_fallback(
+    transcribers: &Mutex<Transcribers>,
+    chunk: &AudioChunk,
+) -> Result<Transcript, TranscriptionFailure> {
+    // Resolver a cadeia antes do await: o lock não atravessa as requisições
+    let (chain, hints) = {
+        let transcribers = transcribers.lock().unwrap();
+        (transcribers.chain(), transcribers.hints.clone())
+    };
+    let chain = chain.map_err(|error| TranscriptionFailure {
+        provider: None,
+        error,
+        attempts: 0,
+    })?;
+
+    let mut failure = None;
+    let mut attempts = 0;
+    for (index, (kind, provider)) in chain.iter().enumerate() {
+        let is_last = index + 1 == chain.len();
+        let max_attempts = if is_last { MAX_ATTEMPTS } else { FAILOVER_ATTEMPTS };
+
+        println!("🔄 Chamando {} para chunk {} ms ({})", provider.name(), chunk.start_ms, chunk.speaker.as_str());
+        match transcribe_with_retry(provider.as_ref(), chunk, &hints, max_attempts).await {
+            Ok(mut transcript) => {
+                correct_glossary(provider.as_ref(), &mut transcript, &hints.glossary);
+                let mut transcribers = transcribers.lock().unwrap();
+                if *kind == transcribers.kind {
+                    transcribers.primary_retry_at = None;
+                } else {
+                    println!("🔀 Chunk {} ms transcrito pelo fallback {}", chunk.start_ms, provider.name());
+                }
+                return Ok(transcript);
+            }
+            Err((error, tries)) => {
+                attempts += tries;
+                let mut transcribers = transcribers.lock().unwrap();
+                if *kind == transcribers.kind && !is_last {
+                    println!("⚠️ {} falhou, usando o fallback por {} s", provider.name(), transcribers.cooldown.as_secs());
+                    transcribers.primary_retry_at = Some(Instant::now() + transcribers.cooldown);
+                }
+                failure = Some((provider.name(), error));
+            }
+        }
+    }
+
+    let (provider, error) = failure.expect("cadeia de backends vazia");
+    Err(TranscriptionFailure {
+        provider: Some(provider),
+        error: error.to_string(),
+        attempts,
+    })
+}
+
+/// Transcrição provisória de um chunk ainda aberto: só o primeiro backend da cadeia,
+/// uma tentativa e sem mexer no cool-down, já que o chunk final vem logo depois
+pub async fn transcribe_partial(
+    transcribers: &Mutex<Transcribers>,
+    chunk: &AudioChunk,
+) -> Result<Transcript, TranscriptionError> {
+    let (chain, hints) = {
+        let transcribers = transcribers.lock().unwrap();
+        (transcribers.chain(), transcribers.hints.clone())
+    };
+    let (_, provider) = chain?
+        .into_iter()
+        .next()
+        .ok_or_else(|| "Nenhum backend de transcrição configurado".to_string())?;
+
+    let mut transcript = provider.transcribe(chunk, &hints).await?;
+    correct_glossary(provider.as_ref(), &mut transcript, &hints.glossary);
+    Ok(transcript)
+}
+
+/// Correção por semelhança com os termos que o backend não recebeu no `prompt`: todos,
+/// se ele não usa `prompt`, ou os que não couberam nele
+fn correct_glossary(provider: &dyn TranscriptionProvider, transcript: &mut Transcript, glossary: &[String]) {
+    let missing = if provider.supports_prompt() {
+        terms_outside_prompt(glossary)
+    } else {
+        glossary
+    };
+    apply_glossary(transcript, missing);
+}
+
+/// Duração aleatória entre zero e `max`
+fn jitter(max: Duration) -> Duration {
+    let random = RandomState::new().build_hasher().finish();
+    max.mul_f64((random % 1000) as f64 / 1000.0)
+}
+
+#[async_trait]
+impl TranscriptionProvider for WhisperApiService {
+    fn name(&self) -> &'static str {
+        WhisperApiService::name(self)
+    }
+
+    fn supports_prompt(&self) -> bool {
+        true
+    }
+
+    async fn transcribe(&self, chunk: &AudioChunk, hints: &TranscriptionHints) -> Result<Transcript, TranscriptionError> {
+        let started = Instant::now();
+        let response = self.transcribe_chunk(chunk, hints).await?;
+        Ok(Transcript::from_whisper(self.name(), chunk.start_ms, started, response))
+    }
+}
+
+/// Sufixo dos WAVs temporários, único por requisição
+static NEXT_SCRATCH_ID: AtomicU64 = AtomicU64::new(0);
+
+/// faster-whisper no processo persistente de `whisper_realtime.py` (modelo carregado
+/// uma vez só); o chunk passa por um WAV temporário
+pub struct LocalWhisperProvider {
+    service: Arc<Mutex<WhisperService>>,
+    scratch_dir: PathBuf,
+}
+
+#[async_trait]
+impl TranscriptionProvider for LocalWhisperProvider {
+    fn name(&self) -> &'static str {
+        "local"
+    }
+
+    fn supports_prompt(&self) -> bool {
+        true
+    }
+
+    async fn transcribe(&self, chunk: &AudioChunk, hints: &TranscriptionHints) -> Result<Transcript, TranscriptionError> {
+        let started = Instant::now();
+        // Parcial e final do mesmo trecho têm o mesmo `start_ms` e podem estar em voo juntos
+        let id = NEXT_SCRATCH_ID.fetch_add(1, Ordering::Relaxed);
+        let path = self
+            .scratch_dir
+            .join(format!("pending_{}_{}_{}.wav", chunk.speaker.as_str(), chunk.start_ms, id));
+        chunk.write_wav(&path)?;
+
+        // O script bloqueia até terminar: fora do runtime async
+        let service = Arc::clone(&self.service);
+        let audio_path = path.to_string_lossy().to_string();
+        let language = hints.language.clone();
+        let prompt = hints.prompt();
+        let result = tokio::task::spawn_blocking(move || {
+            service
+                .lock()
+                .unwrap()
+                .transcribe_realtime(&audio_path, language.as_deref(), prompt.as_deref())
+        })
+            .await
+            .map_err(|e| format!("Erro na transcrição local: {}", e));
+        let _ = std::fs::remove_file(&path);
+
+        let response = result??;
+        Ok(Transcript::from_whisper(self.name(), chunk.start_ms, started, response))
+    }
+}
+
+/// Devolve um texto descrevendo o chunk, sem chamar nenhum modelo
+pub struct MockProvider;
+
+#[async_trait]
+impl TranscriptionProvider for MockProvider {
+    fn name(&self) -> &'static str {
+        "mock"
+    }
+
+    async fn transcribe(&self, chunk: &AudioChunk, hints: &TranscriptionHints) -> Result<Transcript, TranscriptionError> {
+        let started = Instant::now();
+        let text = format!("[mock] {} {}-{} ms", chunk.speaker.as_str(), chunk.start_ms, chunk.end_ms);
+        let duration = (chunk.end_ms - chunk.start_ms) as f64 / 1000.0;
+        // Palavras distribuídas por igual no chunk
+        let word_count = text.split_whitespace().count().max(1) as f64;
+        let words = text
+            .split_whitespace()
+            .enumerate()
+            .map(|(i, word)| WhisperWord {
+                word: word.to_string(),
+                start: duration * i as f64 / word_count,
+                end: duration * (i + 1) as f64 / word_count,
+                probability: Some(1.0),
+            })
+            .collect();
+        let response = WhisperResponse {
+            text: text.clone(),
+            language: Some(hints.language.clone().unwrap_or_else(|| "pt".to_string())),
+            duration: Some(duration),
+            segments: vec![WhisperSegment {
+                start: 0.0,
+                end: duration,
+                text,
+                avg_logprob: Some(0.0),
+                no_speech_prob: Some(0.0),
+                words,
+            }],
+            words: Vec::new(),
+        };
+
+        Ok(Transcript::from_whisper(self.name(), chunk.start_ms, started, response))
+    }
+}
+
+/// Backends na ordem de tentativa, cada um com o tipo que o identifica
+type ProviderChain = Vec<(ProviderKind, Arc<dyn TranscriptionProvider>)>;
+
+/// Backends configurados e qual deles está em uso
+pub struct Transcribers {
+    /// Backend principal
+    pub kind: ProviderKind,
+    pub fallback: Vec<ProviderKind>,
+    /// Idioma (e o que mais vier da sessão) enviado a todos os backends
+    pub hints: TranscriptionHints,
+    cooldown: Duration,
+    /// Até quando o principal fica atrás do fallback, depois de uma falha
+    primary_retry_at: Option<Instant>,
+    pub groq: Option<WhisperApiService>,
+    pub openai: Option<WhisperApiService>,
+    pub local: Arc<Mutex<WhisperService>>,
+    /// Modelo GGML carregado, quando o app é compilado com `whisper-cpp`
+    #[cfg(feature = "whisper-cpp")]
+    pub whisper_cpp: Option<WhisperCppService>,
+    /// Onde o backend local grava os WAVs temporários
+    scratch_dir: PathBuf,
+    /// Backends falsos dos testes, no lugar dos reais do mesmo tipo
+    #[cfg(test)]
+    pub(crate) stubs: Vec<(ProviderKind, Arc<dyn TranscriptionProvider>)>,
+}
+
+impl Transcribers {
+    pub fn new(kind: ProviderKind, options: &TranscriptionOptions, scratch_dir: PathBuf) -> Self {
+        Transcribers {
+            kind,
+            fallback: options.fallback.clone(),
+            hints: TranscriptionHints::new(options),
+            cooldown: Duration::from_secs(options.fallback_cooldown_secs),
+            primary_retry_at: None,
+            groq: None,
+            openai: None,
+            local: Arc::new(Mutex::new(WhisperService::new())),
+            #[cfg(feature = "whisper-cpp")]
+            whisper_cpp: None,
+            scratch_dir,
+            #[cfg(test)]
+            stubs: Vec::new(),
+        }
+    }
+
+    /// Troca o backend principal, que volta a ser tentado primeiro
+    pub fn set_primary(&mut self, kind: ProviderKind) {
+        self.kind = kind;
+        self.primary_retry_at = None;
+    }
+
+    /// Se o backend é o principal ou está no fallback
+    pub fn uses(&self, kind: ProviderKind) -> bool {
+        self.kind == kind || self.fallback.contains(&kind)
+    }
+
+    /// Backends a tentar para o próximo chunk, prontos para usar fora do lock: o principal
+    /// e o fallback, sem os que não estão configurados. Durante o cool-down o principal
+    /// vai para o fim, ainda como última opção.
+    fn chain(&self) -> Result<ProviderChain, String> {
+        let mut kinds = vec![self.kind];
+        for &kind in &self.fallback {
+            if !kinds.contains(&kind) {
+                kinds.push(kind);
+            }
+        }
+        if self.primary_retry_at.is_some_and(|at| Instant::now() < at) {
+            kinds.rotate_left(1);
+        }
+
+        let mut first_error = None;
+        let mut chain = Vec::new();
+        for kind in kinds {
+            match self.provider(kind) {
+                Ok(provider) => chain.push((kind, provider)),
+                Err(e) => {
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+
+        if chain.is_empty() {
+            return Err(first_error.unwrap_or_else(|| "Nenhum backend de transcrição".to_string()));
+        }
+        Ok(chain)
+    }
+
+    fn provider(&self, kind: ProviderKind) -> Result<Arc<dyn TranscriptionProvider>, String> {
+        #[cfg(test)]
+        if let Some((_, stub)) = self.stubs.iter().find(|(stub_kind, _)| *stub_kind == kind) {
+            return Ok(Arc::clone(stub));
+        }
+        match kind {
+            ProviderKind::Groq => self
+                .groq
+                .clone()
+                .map(|groq| Arc::new(groq) as Arc<dyn TranscriptionProvider>)
+                .ok_or_else(|| "Groq não inicializado".to_string()),
+            ProviderKind::OpenAi => self
+                .openai
+                .clone()
+                .map(|openai| Arc::new(openai) as Arc<dyn TranscriptionProvider>)
+                .ok_or_else(|| "OpenAI não inicializado".to_string()),
+            ProviderKind::Local => Ok(Arc::new(LocalWhisperProvider {
+                service: Arc::clone(&self.local),
+                scratch_dir: self.scratch_dir.clone(),
+            })),
+            ProviderKind::WhisperCpp => self.whisper_cpp_provider(),
+            ProviderKind::Mock => Ok(Arc::new(MockProvider)),
+        }
+    }
+
+    #[cfg(feature = "whisper-cpp")]
+    fn whisper_cpp_provider(&self) -> Result<Arc<dyn TranscriptionProvider>, String> {
+        self.whisper_cpp
+            .clone()
+            .map(|service| Arc::new(service) as Arc<dyn TranscriptionProvider>)
+            .ok_or_else(|| "Modelo whisper.cpp não carregado".to_string())
+    }
+
+    #[cfg(not(feature = "whisper-cpp"))]
+    fn whisper_cpp_provider(&self) -> Result<Arc<dyn TranscriptionProvider>, String> {
+        Err(WHISPER_CPP_DISABLED.to_string())
+    }
+}
+
+#[cfg(not(feature = "whisper-cpp"))]
+const WHISPER_CPP_DISABLED: &str = "App compilado sem a feature whisper-cpp";
+
+/// Carrega o modelo GGML e o deixa disponível para o backend `whispercpp`.
+/// Bloqueia durante o carregamento; o lock só é tomado no fim.
+#[cfg(feature = "whisper-cpp")]
+pub fn load_whisper_cpp(transcribers: &Mutex<Transcribers>, model_path: &Path) -> Result<(), String> {
+    let service = WhisperCppService::load(model_path)?;
+    transcribers.lock().unwrap().whisper_cpp = Some(service);
+    Ok(())
+}
+
+#[cfg(not(feature = "whisper-cpp"))]
+pub fn load_whisper_cpp(_transcribers: &Mutex<Transcribers>, _model_path: &Path) -> Result<(), String> {
+    Err(WHISPER_CPP_DISABLED.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::Speaker;
+    use std::sync::atomic::AtomicU32;
+
+    /// Falha as primeiras `failures` chamadas com erro transitório e depois transcreve
+    /// como o mock; cada chamada fica anotada em `calls`
+    struct ScriptedProvider {
+        name: &'static str,
+        failures: AtomicU32,
+        calls: Arc<Mutex<Vec<&'static str>>>,
+    }
+
+    #[async_trait]
+    impl TranscriptionProvider for ScriptedProvider {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        async fn transcribe(&self, chunk: &AudioChunk, hints: &TranscriptionHints) -> Result<Transcript, TranscriptionError> {
+            self.calls.lock().unwrap().push(self.name);
+            let failing = self.failures.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| n.checked_sub(1)).is_ok();
+            if failing {
+                // `retry_after` curto: o backoff de verdade só deixaria o teste lento
+                return Err(TranscriptionError::Transient {
+                    message: format!("{} fora do ar", self.name),
+                    retry_after: Some(Duration::from_millis(1)),
+                });
+            }
+            let mut transcript = MockProvider.transcribe(chunk, hints).await?;
+            transcript.provider = self.name.to_string();
+            Ok(transcript)
+        }
+    }
+
+    /// Cadeia `primary` + `fallback`, com cada backend falhando as vezes indicadas
+    fn transcribers(chain: &[(ProviderKind, &'static str, u32)]) -> (Transcribers, Arc<Mutex<Vec<&'static str>>>) {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let options = TranscriptionOptions {
+            fallback: chain[1..].iter().map(|(kind, _, _)| *kind).collect(),
+            ..Default::default()
+        };
+        let mut transcribers = Transcribers::new(chain[0].0, &options, std::env::temp_dir());
+        for &(kind, name, failures) in chain {
+            let provider = ScriptedProvider {
+                name,
+                failures: AtomicU32::new(failures),
+                calls: Arc::clone(&calls),
+            };
+            transcribers.stubs.push((kind, Arc::new(provider)));
+        }
+        (transcribers, calls)
+    }
+
+    fn chunk(start_ms: u64) -> AudioChunk {
+        AudioChunk {
+            speaker: Speaker::Customer,
+            samples: vec![0.0; 16_000],
+            sample_rate: 16_000,
+            channels: 1,
+            start_ms,
+            end_ms: start_ms + 1_000,
+            path: None,
+        }
+    }
+
+    fn take_calls(calls: &Mutex<Vec<&'static str>>) -> Vec<&'static str> {
+        std::mem::take(&mut *calls.lock().unwrap())
+    }
+
+    #[tokio::test]
+    async fn fails_over_in_chain_order() {
+        let (transcribers, calls) = transcribers(&[
+            (ProviderKind::Groq, "groq", u32::MAX),
+            (ProviderKind::OpenAi, "openai", u32::MAX),
+            (ProviderKind::Mock, "mock", 0),
+        ]);
+
+        let transcript = transcribe_with_fallback(&Mutex::new(transcribers), &chunk(0)).await.ok().unwrap();
+        assert_eq!(transcript.provider, "mock");
+        // Cada backend antes do último desiste depois de `FAILOVER_ATTEMPTS`
+        assert_eq!(take_calls(&calls), ["groq", "groq", "openai", "openai", "mock"]);
+    }
+
+    #[tokio::test]
+    async fn last_provider_gets_every_attempt() {
+        let (transcribers, calls) = transcribers(&[
+            (ProviderKind::Groq, "groq", u32::MAX),
+            (ProviderKind::OpenAi, "openai", u32::MAX),
+        ]);
+
+        let Err(failure) = transcribe_with_fallback(&Mutex::new(transcribers), &chunk(0)).await else {
+            panic!("a cadeia inteira falha");
+        };
+        let calls = take_calls(&calls);
+        assert_eq!(calls.iter().filter(|&&name| name == "groq").count(), FAILOVER_ATTEMPTS as usize);
+        assert_eq!(calls.iter().filter(|&&name| name == "openai").count(), MAX_ATTEMPTS as usize);
+        assert_eq!(failure.provider, Some("openai"));
+        assert_eq!(failure.attempts, FAILOVER_ATTEMPTS + MAX_ATTEMPTS);
+    }
+
+    #[tokio::test]
+    async fn primary_comes_back_after_cooldown() {
+        // O principal cai no primeiro chunk e volta logo depois
+        let (mut transcribers, calls) = transcribers(&[
+            (ProviderKind::Groq, "groq", FAILOVER_ATTEMPTS),
+            (ProviderKind::Mock, "mock", 0),
+        ]);
+        transcribers.cooldown = Duration::from_millis(100);
+        let transcribers = Mutex::new(transcribers);
+
+        let first = transcribe_with_fallback(&transcribers, &chunk(0)).await.ok().unwrap();
+        assert_eq!(first.provider, "mock");
+        assert_eq!(take_calls(&calls), ["groq", "groq", "mock"]);
+
+        // Durante o cool-down o fallback vem primeiro
+        let second = transcribe_with_fallback(&transcribers, &chunk(1_000)).await.ok().unwrap();
+        assert_eq!(second.provider, "mock");
+        assert_eq!(take_calls(&calls), ["mock"]);
+
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        let third = transcribe_with_fallback(&transcribers, &chunk(2_000)).await.ok().unwrap();
+        assert_eq!(third.provider, "groq");
+        assert_eq!(take_calls(&calls), ["groq"]);
+        assert!(transcribers.lock().unwrap().primary_retry_at.is_none());
+    }
+}
@@ -1,33 +1,207 @@
 use serde::{Deserialize, Serialize};
+use crate::transcript::WhisperResponse;
+use crate::transcription::AUTO_LANGUAGE;
 use std::io::{BufRead, BufReader, Write};
-use std::process::{Child, Command, Stdio};
-use std::sync::{Arc, Mutex};
-
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct TranscriptionSegment {
-    pub start: f64,
-    pub end: f64,
-    pub text: String,
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+/// Status que os scripts devolvem junto da transcrição
+#[derive(Debug, Deserialize)]
+struct ScriptStatus {
+    success: bool,
+    error: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct TranscriptionResult {
-    pub success: bool,
-    pub full_text: Option<String>,
-    pub segments: Option<Vec<TranscriptionSegment>>,
-    pub error: Option<String>,
-    pub language: Option<String>,
-    pub duration: Option<f64>,
+/// Variáveis com chaves de API que os scripts Python não precisam e não devem herdar
+const SECRET_ENV_VARS: [&str; 4] = ["GROQ_API_KEY", "OPENAI_API_KEY", "VITE_GROQ_API_KEY", "VITE_OPENAI_API_KEY"];
+
+/// Processo Python para um script local, sem as chaves de API do app no ambiente.
+/// Segredos nunca vão em argv: ficam visíveis a qualquer usuário via `ps`/`/proc/*/cmdline`.
+fn python_command(script: &str) -> Command {
+    let python_cmd = if cfg!(target_os = "windows") {
+        "python"
+    } else {
+        "python3"
+    };
+
+    let mut command = Command::new(python_cmd);
+    command.arg(script);
+    for var in SECRET_ENV_VARS {
+        command.env_remove(var);
+    }
+    command
+}
+
+/// Processo persistente de `whisper_realtime.py`; os pedidos vão pelo stdin
+fn realtime_command() -> Command {
+    python_command("whisper_realtime.py")
+}
+
+/// `whisper_service.py` para um arquivo só
+fn file_command(audio_path: &str, language: Option<&str>) -> Command {
+    let mut command = python_command("whisper_service.py");
+    command.args([audio_path, language.unwrap_or(AUTO_LANGUAGE)]);
+    command
 }
 
+/// Tempo para o modelo carregar e o script responder "ready"
+const READY_TIMEOUT: Duration = Duration::from_secs(120);
+/// Tempo máximo de uma transcrição no processo persistente
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Requisição do protocolo de `whisper_realtime.py`: um JSON por linha no stdin,
+/// respondido por um JSON por linha no stdout com o mesmo `id`
 #[derive(Debug, Serialize, Deserialize)]
 struct WhisperCommand {
+    id: u64,
     action: String,
     audio_path: Option<String>,
+    language: Option<String>,
+    /// `initial_prompt` do Faster-Whisper (glossário)
+    prompt: Option<String>,
+}
+
+/// Primeira linha do stdout: `{"status": "ready"}` quando o modelo carregou
+#[derive(Debug, Deserialize)]
+struct ReadyLine {
+    status: Option<String>,
+    error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WhisperReply {
+    id: Option<u64>,
+    success: bool,
+    error: Option<String>,
+}
+
+/// Processo Python com o modelo carregado. O stdout é lido por uma thread própria
+/// para que a espera por resposta possa ter timeout.
+struct RealtimeProcess {
+    child: Child,
+    stdin: ChildStdin,
+    lines: mpsc::Receiver<String>,
+    next_id: u64,
+}
+
+impl RealtimeProcess {
+    fn spawn() -> Result<Self, String> {
+        let mut child = realtime_command()
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .map_err(|e| format!("❌ Erro ao iniciar processo Python: {}", e))?;
+
+        let stdin = child.stdin.take().ok_or("Processo Whisper sem stdin")?;
+        let stdout = child.stdout.take().ok_or("Processo Whisper sem stdout")?;
+
+        let (tx, lines) = mpsc::channel();
+        std::thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else { break };
+                if tx.send(line).is_err() {
+                    break;
+                }
+            }
+            // Canal fechado = processo terminou
+        });
+
+        let mut process = RealtimeProcess {
+            child,
+            stdin,
+            lines,
+            next_id: 1,
+        };
+
+        // Só `{"status": "ready"}` serve; qualquer outra coisa é erro ao carregar o modelo
+        let ready = match process.lines.recv_timeout(READY_TIMEOUT) {
+            Ok(line) => match serde_json::from_str::<ReadyLine>(&line) {
+                Ok(ReadyLine { status: Some(status), .. }) if status == "ready" => Ok(()),
+                Ok(ReadyLine { error: Some(error), .. }) => Err(error),
+                _ => Err(format!("resposta inesperada: {}", line)),
+            },
+            Err(mpsc::RecvTimeoutError::Timeout) => Err("tempo esgotado ao carregar o modelo".to_string()),
+            Err(mpsc::RecvTimeoutError::Disconnected) => Err("o processo Python terminou (veja o stderr)".to_string()),
+        };
+        if let Err(e) = ready {
+            process.kill();
+            return Err(format!("❌ Whisper não ficou pronto: {}", e));
+        }
+        println!("✅ Whisper pronto");
+
+        Ok(process)
+    }
+
+    fn is_alive(&mut self) -> bool {
+        matches!(self.child.try_wait(), Ok(None))
+    }
+
+    fn send(
+        &mut self,
+        action: &str,
+        audio_path: Option<&str>,
+        language: Option<&str>,
+        prompt: Option<&str>,
+    ) -> Result<String, RequestError> {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let command = WhisperCommand {
+            id,
+            action: action.to_string(),
+            audio_path: audio_path.map(str::to_string),
+            language: language.map(str::to_string),
+            prompt: prompt.map(str::to_string),
+        };
+        let line = serde_json::to_string(&command).map_err(|e| RequestError::Failed(e.to_string()))?;
+        writeln!(self.stdin, "{}", line)
+            .and_then(|_| self.stdin.flush())
+            .map_err(|_| RequestError::Crashed)?;
+
+        let deadline = Instant::now() + REQUEST_TIMEOUT;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let line = match self.lines.recv_timeout(remaining) {
+                Ok(line) => line,
+                Err(mpsc::RecvTimeoutError::Timeout) => return Err(RequestError::Timeout),
+                Err(mpsc::RecvTimeoutError::Disconnected) => return Err(RequestError::Crashed),
+            };
+
+            // Respostas atrasadas de requisições que já expiraram são descartadas
+            match serde_json::from_str::<WhisperReply>(&line) {
+                Ok(reply) if reply.id == Some(id) => {
+                    if !reply.success {
+                        return Err(RequestError::Failed(
+                            reply.error.unwrap_or_else(|| "Erro na transcrição local".to_string()),
+                        ));
+                    }
+                    return Ok(line);
+                }
+                Ok(_) => continue,
+                Err(_) => println!("⚠️ Saída inesperada do Whisper: {}", line),
+            }
+        }
+    }
+
+    fn kill(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+enum RequestError {
+    /// O processo morreu ou fechou o stdout
+    Crashed,
+    /// Sem resposta dentro de `REQUEST_TIMEOUT`
+    Timeout,
+    /// O script respondeu com erro
+    Failed(String),
 }
 
 pub struct WhisperService {
-    process: Option<Arc<Mutex<Child>>>,
+    process: Option<RealtimeProcess>,
 }
 
 impl WhisperService {
@@ -35,18 +209,11 @@ impl WhisperService {
         WhisperService { process: None }
     }
 
-    /// Transcrição simples (um arquivo por vez)
-    pub fn transcribe_file(&self, audio_path: &str, language: &str) -> Result<TranscriptionResult, String> {
-        let python_cmd = if cfg!(target_os = "windows") {
-            "python"
-        } else {
-            "python3"
-        };
-
+    /// Transcrição simples (um arquivo por vez); sem `language`, o idioma é detectado
+    pub fn transcribe_file(&self, audio_path: &str, language: Option<&str>) -> Result<WhisperResponse, String> {
         println!("🎯 Transcrevendo arquivo: {}", audio_path);
 
-        let output = Command::new(python_cmd)
-            .args(&["whisper_service.py", audio_path, language])
+        let output = file_command(audio_path, language)
             .output()
             .map_err(|e| format!("❌ Erro ao executar Python: {}. Certifique-se que Python está instalado e no PATH.", e))?;
 
@@ -59,62 +226,87 @@ impl WhisperService {
     .unwrap_or_else(|_| String::from_utf8_lossy(&output.stdout).to_string());
         println!("📝 Resposta do Python: {}", stdout);
 
-        let result: TranscriptionResult = serde_json::from_str(&stdout)
+        let status: ScriptStatus = serde_json::from_str(&stdout)
             .map_err(|e| format!("❌ Erro ao parsear JSON: {}. Output: {}", e, stdout))?;
+        if !status.success {
+            return Err(status.error.unwrap_or_else(|| "Erro na transcrição local".to_string()));
+        }
 
-        Ok(result)
+        serde_json::from_str(&stdout)
+            .map_err(|e| format!("❌ Erro ao parsear JSON: {}. Output: {}", e, stdout))
     }
 
     /// Iniciar modo streaming (processo persistente)
     pub fn start_realtime(&mut self) -> Result<(), String> {
-        if self.process.is_some() {
-            return Ok(()); // Já está rodando
+        if let Some(process) = self.process.as_mut() {
+            if process.is_alive() {
+                return Ok(()); // Já está rodando
+            }
+            println!("⚠️ Processo Whisper terminou inesperadamente - reiniciando");
+            self.process = None;
         }
 
-        let python_cmd = if cfg!(target_os = "windows") {
-            "python"
-        } else {
-            "python3"
-        };
-
         println!("🚀 Iniciando Whisper em modo real-time...");
+        self.process = Some(RealtimeProcess::spawn()?);
 
-        let mut child = Command::new(python_cmd)
-            .args(&["whisper_realtime.py"])
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn()
-            .map_err(|e| format!("❌ Erro ao iniciar processo Python: {}", e))?;
+        Ok(())
+    }
 
-        // Ler linha "ready"
-        if let Some(ref mut stdout) = child.stdout {
-            let mut reader = BufReader::new(stdout);
-            let mut ready_line = String::new();
-            reader
-                .read_line(&mut ready_line)
-                .map_err(|e| format!("❌ Erro ao ler ready: {}", e))?;
+    /// Transcreve pelo processo persistente (modelo já carregado), iniciando-o se preciso.
+    /// Se o processo cair no meio, é reiniciado e a requisição é repetida uma vez.
+    /// Sem `language`, o idioma é detectado.
+    pub fn transcribe_realtime(
+        &mut self,
+        audio_path: &str,
+        language: Option<&str>,
+        prompt: Option<&str>,
+    ) -> Result<WhisperResponse, String> {
+        for attempt in 0..2 {
+            self.start_realtime()?;
+            let process = self.process.as_mut().unwrap();
 
-            println!("✅ Whisper pronto: {}", ready_line);
+            match process.send("transcribe", Some(audio_path), language, prompt) {
+                Ok(line) => {
+                    return serde_json::from_str(&line)
+                        .map_err(|e| format!("❌ Erro ao parsear JSON: {}. Output: {}", e, line));
+                }
+                Err(RequestError::Failed(e)) => return Err(e),
+                Err(RequestError::Timeout) => {
+                    // Um processo travado não serve para o próximo chunk
+                    process.kill();
+                    self.process = None;
+                    return Err("Tempo esgotado na transcrição local".to_string());
+                }
+                Err(RequestError::Crashed) => {
+                    process.kill();
+                    self.process = None;
+                    if attempt == 0 {
+                        println!("⚠️ Processo Whisper caiu - reiniciando");
+                    }
+                }
+            }
         }
 
-        self.process = Some(Arc::new(Mutex::new(child)));
-
-        Ok(())
+        Err("Processo Whisper caiu durante a transcrição".to_string())
     }
 
     /// Parar processo streaming
     pub fn stop_realtime(&mut self) -> Result<(), String> {
-        if let Some(process) = self.process.take() {
-            let mut child = process.lock().unwrap();
-            
+        if let Some(mut process) = self.process.take() {
             // Enviar comando de saída
-            if let Some(stdin) = child.stdin.as_mut() {
-                let _ = writeln!(stdin, r#"{{"action": "exit"}}"#);
+            let command = WhisperCommand {
+                id: process.next_id,
+                action: "exit".to_string(),
+                audio_path: None,
+                language: None,
+                prompt: None,
+            };
+            if let Ok(line) = serde_json::to_string(&command) {
+                let _ = writeln!(process.stdin, "{}", line);
             }
 
             // Aguardar término
-            let _ = child.wait();
+            let _ = process.child.wait();
             println!("⏹️ Processo Whisper finalizado");
         }
         Ok(())
@@ -126,3 +318,42 @@ impl Drop for WhisperService {
         let _ = self.stop_realtime();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::OsStr;
+
+    #[test]
+    fn api_keys_stay_out_of_python_processes() {
+        let secret = "gsk_segredo_de_teste";
+        for var in SECRET_ENV_VARS {
+            std::env::set_var(var, secret);
+        }
+
+        let commands = [
+            realtime_command(),
+            file_command("/tmp/chunk.wav", Some("pt")),
+            file_command("/tmp/chunk.wav", None),
+        ];
+        for command in &commands {
+            let args: Vec<&OsStr> = command.get_args().collect();
+            assert!(
+                args.iter().all(|arg| !arg.to_string_lossy().contains(secret)),
+                "chave no argv: {:?}",
+                args
+            );
+
+            let envs: Vec<(&OsStr, Option<&OsStr>)> = command.get_envs().collect();
+            assert!(
+                envs.iter().flat_map(|(_, value)| value).all(|value| !value.to_string_lossy().contains(secret)),
+                "chave no ambiente: {:?}",
+                envs
+            );
+            // Removidas explicitamente: o processo não herda do app
+            for var in SECRET_ENV_VARS {
+                assert!(envs.contains(&(OsStr::new(var), None)), "{} herdada", var);
+            }
+        }
+    }
+}
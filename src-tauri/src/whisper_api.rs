@@ -0,0 +1,298 @@
+use reqwest::StatusCode;
+use std::time::Duration;
+use crate::audio::AudioChunk;
+use crate::transcript::WhisperResponse;
+use crate::transcription::{TranscriptionError, TranscriptionHints};
+
+const GROQ_API_URL: &str = "https://api.groq.com/openai/v1";
+const GROQ_MODEL: &str = "whisper-large-v3-turbo";
+const OPENAI_API_URL: &str = "https://api.openai.com/v1";
+const OPENAI_MODEL: &str = "whisper-1";
+/// Tempo máximo de uma requisição; chunks têm no máximo algumas dezenas de segundos
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Cliente de `audio/transcriptions`. O Groq segue o formato da API da OpenAI,
+/// então o mesmo cliente atende os dois.
+#[derive(Clone)]
+pub struct WhisperApiService {
+    name: &'static str,
+    api_key: String,
+    base_url: String,
+    model: &'static str,
+    client: reqwest::Client,
+}
+
+impl WhisperApiService {
+    /// Whisper do Groq (`whisper-large-v3-turbo`)
+    pub fn groq(api_key: String) -> Self {
+        Self::with_endpoint("groq", api_key, GROQ_API_URL.to_string(), GROQ_MODEL)
+    }
+
+    /// Whisper da própria OpenAI (`whisper-1`)
+    pub fn openai(api_key: String) -> Self {
+        Self::with_endpoint("openai", api_key, OPENAI_API_URL.to_string(), OPENAI_MODEL)
+    }
+
+    fn with_endpoint(name: &'static str, api_key: String, base_url: String, model: &'static str) -> Self {
+        WhisperApiService {
+            name,
+            api_key,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            model,
+            client: reqwest::Client::builder()
+                .timeout(REQUEST_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Transcreve um chunk vindo da captura, enviando o WAV direto da memória
+    pub async fn transcribe_chunk(
+        &self,
+        chunk: &AudioChunk,
+        hints: &TranscriptionHints,
+    ) -> Result<WhisperResponse, TranscriptionError> {
+        self.transcribe_bytes(chunk.to_wav_bytes()?, chunk.file_name(), hints).await
+    }
+
+    async fn transcribe_bytes(
+        &self,
+        audio: Vec<u8>,
+        file_name: String,
+        hints: &TranscriptionHints,
+    ) -> Result<WhisperResponse, TranscriptionError> {
+        let file = reqwest::multipart::Part::bytes(audio)
+            .file_name(file_name)
+            .mime_str("audio/wav")
+            .map_err(|e| format!("Erro: {}", e))?;
+
+        let mut form = reqwest::multipart::Form::new()
+            .part("file", file)
+            .text("model", self.model)
+            .text("response_format", "verbose_json")
+            // Com as duas granularidades a resposta traz os segmentos e também as palavras
+            .text("timestamp_granularities[]", "segment")
+            .text("timestamp_granularities[]", "word");
+        // Sem `language` a API detecta o idioma
+        if let Some(language) = &hints.language {
+            form = form.text("language", language.clone());
+        }
+        if let Some(prompt) = hints.prompt() {
+            form = form.text("prompt", prompt);
+        }
+
+        let response = self
+            .client
+            .post(format!("{}/audio/transcriptions", self.base_url))
+            .header("Authorization", format!("Bearer {}", self.api_key))
+            .multipart(form)
+            .send()
+            .await
+            .map_err(|e| {
+                // Timeout e falha de conexão costumam passar; erro ao montar a requisição não
+                let message = format!("❌ Erro na requisição ao {}: {}", self.name, e);
+                if e.is_timeout() || e.is_connect() || e.is_request() {
+                    TranscriptionError::transient(message)
+                } else {
+                    TranscriptionError::Permanent(message)
+                }
+            })?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let retry_after = retry_after(&response);
+            let body = response.text().await.unwrap_or_default();
+            println!("❌ {} respondeu {}: {}", self.name, status, body);
+            let message = format!("Erro na API do {} ({}): {}", self.name, status, body);
+            return Err(if status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error() {
+                TranscriptionError::Transient { message, retry_after }
+            } else {
+                TranscriptionError::Permanent(message)
+            });
+        }
+
+        let transcription: WhisperResponse = response
+            .json()
+            .await
+            .map_err(|e| TranscriptionError::transient(format!("❌ Erro parse JSON: {}", e)))?;
+
+        println!("✅ Transcrição sucesso: {:?}", transcription.text);
+
+        Ok(transcription)
+    }
+}
+
+/// `retry-after` em segundos (o formato de data HTTP não é usado por essas APIs)
+fn retry_after(response: &reqwest::Response) -> Option<Duration> {
+    response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|seconds| seconds.is_finite() && *seconds >= 0.0)
+        .map(Duration::from_secs_f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::Speaker;
+    use crate::transcription::{TranscriptionOptions, TranscriptionProvider};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+    use tokio::task::JoinHandle;
+
+    const VERBOSE_JSON: &str = r#"{
+        "text": " Olá, tudo bem?",
+        "language": "portuguese",
+        "duration": 1.5,
+        "segments": [
+            {"start": 0.0, "end": 0.8, "text": " Olá,", "avg_logprob": -0.1, "no_speech_prob": 0.01},
+            {"start": 0.8, "end": 1.5, "text": " tudo bem?", "avg_logprob": -0.2, "no_speech_prob": 0.02}
+        ],
+        "words": [
+            {"word": "Olá", "start": 0.0, "end": 0.6},
+            {"word": "tudo", "start": 0.8, "end": 1.1},
+            {"word": "bem", "start": 1.1, "end": 1.4}
+        ]
+    }"#;
+
+    /// Servidor HTTP local de uma requisição só: responde `status` com `headers` e `body`
+    /// e devolve a requisição recebida
+    async fn mock_server(status: &str, headers: &str, body: &str) -> (String, JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let response = format!(
+            "HTTP/1.1 {}\r\n{}Content-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            headers,
+            body.len(),
+            body
+        );
+
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buffer = [0u8; 8192];
+            loop {
+                let read = socket.read(&mut buffer).await.unwrap();
+                if read == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buffer[..read]);
+                if request_complete(&request) {
+                    break;
+                }
+            }
+            socket.write_all(response.as_bytes()).await.unwrap();
+            socket.shutdown().await.unwrap();
+            String::from_utf8_lossy(&request).to_string()
+        });
+
+        (base_url, server)
+    }
+
+    /// Cabeçalhos recebidos e corpo com o tamanho do `content-length`
+    fn request_complete(request: &[u8]) -> bool {
+        let Some(header_end) = request.windows(4).position(|w| w == b"\r\n\r\n") else {
+            return false;
+        };
+        let headers = String::from_utf8_lossy(&request[..header_end]).to_lowercase();
+        let content_length = headers
+            .lines()
+            .find_map(|line| line.strip_prefix("content-length:"))
+            .and_then(|value| value.trim().parse::<usize>().ok())
+            .unwrap_or(0);
+        request.len() >= header_end + 4 + content_length
+    }
+
+    fn service(base_url: String) -> WhisperApiService {
+        WhisperApiService::with_endpoint("groq", "test-key".to_string(), base_url, GROQ_MODEL)
+    }
+
+    fn chunk() -> AudioChunk {
+        AudioChunk {
+            speaker: Speaker::Customer,
+            samples: vec![0.0; 16_000],
+            sample_rate: 16_000,
+            channels: 1,
+            start_ms: 2_000,
+            end_ms: 3_000,
+            path: None,
+        }
+    }
+
+    fn hints() -> TranscriptionHints {
+        TranscriptionHints::new(&TranscriptionOptions {
+            language: "pt".to_string(),
+            glossary: vec!["HubSpot".to_string()],
+            ..TranscriptionOptions::default()
+        })
+    }
+
+    /// Valor de um campo de texto do formulário multipart
+    fn form_field<'a>(request: &'a str, name: &str) -> Option<&'a str> {
+        let start = request.find(&format!("name=\"{}\"\r\n\r\n", name))?;
+        let value = &request[start..];
+        let value = &value[value.find("\r\n\r\n")? + 4..];
+        Some(&value[..value.find("\r\n")?])
+    }
+
+    #[tokio::test]
+    async fn sends_multipart_form_and_parses_verbose_json() {
+        let (base_url, server) = mock_server("200 OK", "", VERBOSE_JSON).await;
+
+        let transcript = service(base_url).transcribe(&chunk(), &hints()).await.unwrap();
+        let request = server.await.unwrap();
+
+        assert!(request.starts_with("POST /audio/transcriptions "));
+        assert!(request.to_lowercase().contains("authorization: bearer test-key"));
+        assert!(request.contains("name=\"file\"; filename=\"chunk_customer_2000.wav\""));
+        assert_eq!(form_field(&request, "model"), Some(GROQ_MODEL));
+        assert_eq!(form_field(&request, "response_format"), Some("verbose_json"));
+        assert_eq!(form_field(&request, "language"), Some("pt"));
+        assert_eq!(form_field(&request, "prompt"), Some("HubSpot."));
+
+        assert_eq!(transcript.provider, "groq");
+        assert_eq!(transcript.language.as_deref(), Some("pt"));
+        assert_eq!(transcript.segments.len(), 2);
+        assert_eq!(transcript.segments[0].text, "Olá,");
+        assert_eq!(transcript.segments[1].start_ms, 2_800);
+        assert_eq!(transcript.segments[1].end_ms, 3_500);
+        assert_eq!(transcript.segments[0].words.len(), 1);
+        assert_eq!(transcript.segments[1].words.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn rate_limit_is_transient_with_retry_after() {
+        let (base_url, server) =
+            mock_server("429 Too Many Requests", "Retry-After: 7\r\n", r#"{"error":"rate limit"}"#).await;
+
+        let error = service(base_url).transcribe_chunk(&chunk(), &hints()).await.unwrap_err();
+        server.await.unwrap();
+
+        match error {
+            TranscriptionError::Transient { retry_after, .. } => {
+                assert_eq!(retry_after, Some(Duration::from_secs(7)));
+            }
+            other => panic!("esperado erro transitório, veio {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn client_error_is_permanent() {
+        let (base_url, server) = mock_server("401 Unauthorized", "", r#"{"error":"invalid key"}"#).await;
+
+        let error = service(base_url).transcribe_chunk(&chunk(), &hints()).await.unwrap_err();
+        server.await.unwrap();
+
+        assert!(matches!(error, TranscriptionError::Permanent(_)), "{:?}", error);
+    }
+}
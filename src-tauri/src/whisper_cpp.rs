@@ -0,0 +1,176 @@
+use async_trait::async_trait;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Instant;
+use whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters, WhisperState};
+use crate::audio::{downmix_to_mono, AudioChunk, Resampler, WHISPER_SAMPLE_RATE};
+use crate::transcript::{Transcript, WhisperResponse, WhisperSegment, WhisperWord};
+use crate::transcription::{TranscriptionError, TranscriptionHints, TranscriptionProvider, AUTO_LANGUAGE};
+
+/// whisper.cpp embutido no app (feature `whisper-cpp`): modelo GGML carregado uma vez,
+/// inferência na CPU, sem Python
+#[derive(Clone)]
+pub struct WhisperCppService {
+    context: Arc<WhisperContext>,
+}
+
+impl WhisperCppService {
+    /// Carrega o modelo GGML (ex.: `ggml-base.bin`); pode levar alguns segundos
+    pub fn load(model_path: &Path) -> Result<Self, String> {
+        println!("🧠 Carregando modelo whisper.cpp: {:?}", model_path);
+
+        let path = model_path
+            .to_str()
+            .ok_or_else(|| format!("Caminho do modelo inválido: {:?}", model_path))?;
+        let context = WhisperContext::new_with_params(path, WhisperContextParameters::default())
+            .map_err(|e| format!("❌ Erro ao carregar modelo whisper.cpp: {}", e))?;
+
+        println!("✅ Modelo whisper.cpp carregado");
+        Ok(WhisperCppService {
+            context: Arc::new(context),
+        })
+    }
+
+    /// Transcreve samples mono a 16 kHz (`language` pode ser `auto`). Bloqueia até o fim
+    /// da inferência.
+    fn transcribe_samples(&self, samples: &[f32], language: &str, prompt: Option<&str>) -> Result<WhisperResponse, String> {
+        let mut state = self
+            .context
+            .create_state()
+            .map_err(|e| format!("Erro ao criar estado do whisper.cpp: {}", e))?;
+
+        let threads = std::thread::available_parallelism().map_or(4, |n| n.get()) as i32;
+        let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
+        params.set_language(Some(language));
+        params.set_n_threads(threads);
+        params.set_print_special(false);
+        params.set_print_progress(false);
+        params.set_print_realtime(false);
+        params.set_print_timestamps(false);
+        params.set_token_timestamps(true);
+        if let Some(prompt) = prompt {
+            params.set_initial_prompt(prompt);
+        }
+
+        state
+            .full(params, samples)
+            .map_err(|e| format!("❌ Erro na transcrição whisper.cpp: {}", e))?;
+
+        let n_segments = state
+            .full_n_segments()
+            .map_err(|e| format!("Erro ao ler segmentos: {}", e))?;
+
+        let mut segments = Vec::new();
+        for i in 0..n_segments {
+            let text = state
+                .full_get_segment_text(i)
+                .map_err(|e| format!("Erro ao ler segmento {}: {}", i, e))?;
+            // Tempos em centésimos de segundo
+            let start = state.full_get_segment_t0(i).unwrap_or(0) as f64 / 100.0;
+            let end = state.full_get_segment_t1(i).unwrap_or(0) as f64 / 100.0;
+
+            // Equivalente ao avg_logprob do faster-whisper
+            let n_tokens = state.full_n_tokens(i).unwrap_or(0);
+            let logprobs: Vec<f64> = (0..n_tokens)
+                .filter_map(|t| state.full_get_token_prob(i, t).ok())
+                .filter(|&p| p > 0.0)
+                .map(|p| (p as f64).ln())
+                .collect();
+            let avg_logprob = (!logprobs.is_empty()).then(|| logprobs.iter().sum::<f64>() / logprobs.len() as f64);
+
+            segments.push(WhisperSegment {
+                start,
+                end,
+                text,
+                avg_logprob,
+                no_speech_prob: None,
+                words: self.segment_words(&state, i, n_tokens),
+            });
+        }
+
+        let language = state
+            .full_lang_id_from_state()
+            .ok()
+            .and_then(whisper_rs::get_lang_str)
+            .map(str::to_string);
+
+        Ok(WhisperResponse {
+            text: segments.iter().map(|s| s.text.trim()).collect::<Vec<_>>().join(" "),
+            language,
+            duration: Some(samples.len() as f64 / WHISPER_SAMPLE_RATE as f64),
+            segments,
+            words: Vec::new(),
+        })
+    }
+
+    /// Junta os tokens do segmento em palavras: um token que começa com espaço abre uma
+    /// palavra nova. A confiança da palavra é a do seu token menos provável.
+    fn segment_words(&self, state: &WhisperState, segment: i32, n_tokens: i32) -> Vec<WhisperWord> {
+        let eot = self.context.token_eot();
+        let mut words: Vec<WhisperWord> = Vec::new();
+
+        for t in 0..n_tokens {
+            let Ok(data) = state.full_get_token_data(segment, t) else {
+                continue;
+            };
+            let Ok(text) = state.full_get_token_text_lossy(segment, t) else {
+                continue;
+            };
+            // Tokens especiais (início/fim, timestamps) não são texto
+            if data.id >= eot {
+                continue;
+            }
+
+            // Tempos em centésimos de segundo
+            let (start, end) = (data.t0 as f64 / 100.0, data.t1 as f64 / 100.0);
+            let probability = data.p as f64;
+            match words.last_mut() {
+                Some(word) if !text.starts_with(' ') => {
+                    word.word.push_str(&text);
+                    word.end = end;
+                    word.probability = word.probability.map(|p| p.min(probability));
+                }
+                _ => words.push(WhisperWord {
+                    word: text.trim().to_string(),
+                    start,
+                    end,
+                    probability: Some(probability),
+                }),
+            }
+        }
+
+        words
+    }
+}
+
+#[async_trait]
+impl TranscriptionProvider for WhisperCppService {
+    fn name(&self) -> &'static str {
+        "whispercpp"
+    }
+
+    fn supports_prompt(&self) -> bool {
+        true
+    }
+
+    async fn transcribe(&self, chunk: &AudioChunk, hints: &TranscriptionHints) -> Result<Transcript, TranscriptionError> {
+        let started = Instant::now();
+
+        let mut samples = downmix_to_mono(&chunk.samples, chunk.channels);
+        if chunk.sample_rate != WHISPER_SAMPLE_RATE {
+            samples = Resampler::new(chunk.sample_rate, WHISPER_SAMPLE_RATE).process(&samples);
+        }
+
+        // Inferência pesada na CPU: fora do runtime async
+        let service = self.clone();
+        let language = hints.language.clone().unwrap_or_else(|| AUTO_LANGUAGE.to_string());
+        let prompt = hints.prompt();
+        let response = tokio::task::spawn_blocking(move || {
+            service.transcribe_samples(&samples, &language, prompt.as_deref())
+        })
+            .await
+            .map_err(|e| format!("Erro na transcrição whisper.cpp: {}", e))??;
+
+        Ok(Transcript::from_whisper(self.name(), chunk.start_ms, started, response))
+    }
+}
@@ -1,12 +1,17 @@
 import sys
 import json
-import time
 import os
 from faster_whisper import WhisperModel
-from collections import deque
+
+# Protocolo (um JSON por linha):
+#   stdin:  {"id": 1, "action": "transcribe", "audio_path": "...", "language": "pt", "prompt": "..."}
+#           (language null = detectar; prompt = glossário, opcional)
+#   stdout: {"id": 1, "success": true, "text": "...", "language": "pt", "duration": 1.2, "segments": [...]}
+# A primeira linha do stdout é {"status": "ready"}, quando o modelo termina de carregar,
+# ou {"status": "error", "error": "..."} se ele não carregou.
 
 class RealtimeTranscriber:
-    def __init__(self, model_size="base", language="pt"):
+    def __init__(self, model_size="base", language=None):
         # Usar diretório de usuário para cache dos modelos
         models_dir = os.path.join(os.path.expanduser("~"), ".cache", "whisper-models")
         os.makedirs(models_dir, exist_ok=True)
@@ -18,74 +23,81 @@ class RealtimeTranscriber:
             download_root=models_dir  # MUDANÇA: usar diretório do usuário
         )
         self.language = language
-        self.buffer = deque(maxlen=10)
         
-    def transcribe_chunk(self, audio_path):
+    def transcribe_chunk(self, audio_path, language=None, prompt=None):
         """
         Transcreve um chunk de áudio
         """
-        try:
-            segments, info = self.model.transcribe(
-                audio_path,
-                language=self.language,
-                beam_size=5,
-                vad_filter=True,
-                vad_parameters=dict(
-                    min_silence_duration_ms=300
-                )
+        segments, info = self.model.transcribe(
+            audio_path,
+            language=language or self.language,
+            initial_prompt=prompt,
+            beam_size=5,
+            word_timestamps=True,
+            vad_filter=True,
+            vad_parameters=dict(
+                min_silence_duration_ms=300
             )
-            
-            chunks = []
-            for segment in segments:
-                chunk = {
-                    "timestamp": time.time(),
-                    "start": segment.start,
-                    "end": segment.end,
-                    "text": segment.text.strip()
-                }
-                chunks.append(chunk)
-                self.buffer.append(chunk)
-            
-            return {
-                "success": True,
-                "chunks": chunks,
-                "context": list(self.buffer)
-            }
-            
-        except Exception as e:
-            return {
-                "success": False,
-                "error": str(e)
-            }
+        )
+        
+        result_segments = []
+        for segment in segments:
+            result_segments.append({
+                "start": segment.start,
+                "end": segment.end,
+                "text": segment.text.strip(),
+                "avg_logprob": segment.avg_logprob,
+                "no_speech_prob": segment.no_speech_prob,
+                "words": [
+                    {"word": w.word, "start": w.start, "end": w.end, "probability": w.probability}
+                    for w in (segment.words or [])
+                ]
+            })
+        
+        return {
+            "success": True,
+            "text": " ".join(seg["text"] for seg in result_segments),
+            "language": info.language,
+            "duration": info.duration,
+            "segments": result_segments
+        }
+
+def reply(command_id, result):
+    result["id"] = command_id
+    print(json.dumps(result, ensure_ascii=False), flush=True)
 
 def main():
     """
     Modo interativo: recebe comandos via stdin
     """
-    transcriber = RealtimeTranscriber()
+    try:
+        transcriber = RealtimeTranscriber()
+    except Exception as e:
+        print(json.dumps({"status": "error", "error": str(e)}), flush=True)
+        sys.exit(1)
     
     # Sinalizar que está pronto
     print(json.dumps({"status": "ready"}), flush=True)
     
     for line in sys.stdin:
+        command_id = None
         try:
             command = json.loads(line.strip())
+            command_id = command.get("id")
             
             if command["action"] == "transcribe":
-                audio_path = command["audio_path"]
-                result = transcriber.transcribe_chunk(audio_path)
-                print(json.dumps(result, ensure_ascii=False), flush=True)
+                result = transcriber.transcribe_chunk(command["audio_path"], command.get("language"), command.get("prompt"))
+                reply(command_id, result)
                 
             elif command["action"] == "change_language":
                 transcriber.language = command["language"]
-                print(json.dumps({"success": True}), flush=True)
+                reply(command_id, {"success": True})
                 
             elif command["action"] == "exit":
                 break
                 
         except Exception as e:
-            error = {"success": False, "error": str(e)}
-            print(json.dumps(error), flush=True)
+            reply(command_id, {"success": False, "error": str(e)})
 
 if __name__ == "__main__":
     main()
@@ -27,6 +27,7 @@ def transcribe_audio(audio_path, language="pt"):
             audio_path,
             language=language,
             beam_size=5,
+            word_timestamps=True,
             vad_filter=True,
             vad_parameters=dict(
                 min_silence_duration_ms=500
@@ -39,7 +40,13 @@ def transcribe_audio(audio_path, language="pt"):
             transcription.append({
                 "start": segment.start,
                 "end": segment.end,
-                "text": segment.text.strip()
+                "text": segment.text.strip(),
+                "avg_logprob": segment.avg_logprob,
+                "no_speech_prob": segment.no_speech_prob,
+                "words": [
+                    {"word": w.word, "start": w.start, "end": w.end, "probability": w.probability}
+                    for w in (segment.words or [])
+                ]
             })
         
         # Retornar JSON
@@ -66,11 +73,13 @@ if __name__ == "__main__":
     if len(sys.argv) < 2:
         print(json.dumps({
             "success": False,
-            "error": "Uso: python whisper_service.py <caminho_audio> [idioma]"
+            "error": "Uso: python whisper_service.py <caminho_audio> [idioma|auto]"
         }))
         sys.exit(1)
     
     audio_path = sys.argv[1]
     language = sys.argv[2] if len(sys.argv) > 2 else "pt"
+    if language == "auto":
+        language = None  # Faster-Whisper detecta o idioma
     
     transcribe_audio(audio_path, language)
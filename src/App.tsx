@@ -2,12 +2,21 @@ import { useState, useEffect } from "react";
 import { TranscriptionView } from "./components/TranscriptionView";
 import { AnalysisPanel } from "./components/AnalysisPanel";
 import { audioService } from "./services/tauri";
-import { Transcription, Analysis } from "./types";
+import { Transcription, Analysis, AudioError } from "./types";
 
 // IMPORTANTE: Nunca commitar a chave real no código!
 // Use variáveis de ambiente em produção
 const OPENAI_API_KEY = import.meta.env.VITE_OPENAI_API_KEY;
 
+// Erros de áudio chegam tipados ({ kind, message }) do backend
+const formatError = (err: unknown): string => {
+  if (err && typeof err === "object" && "kind" in err) {
+    const audioError = err as AudioError;
+    return audioError.message ?? audioError.kind;
+  }
+  return String(err);
+};
+
 function App() {
   const [isRealtimeActive, setIsRealtimeActive] = useState(false);
   const [isOpenAIReady, setIsOpenAIReady] = useState(false);
@@ -37,10 +46,11 @@ function App() {
     let unlistenChunk: any;
     let unlistenTranscription: any;
     let unlistenAnalysis: any;
+    let unlistenDeviceError: any;
 
     const setupListeners = async () => {
-      unlistenChunk = await audioService.onNewChunk((chunkPath) => {
-        console.log("📦 Novo chunk:", chunkPath);
+      unlistenChunk = await audioService.onNewChunk((chunk) => {
+        console.log("📦 Novo chunk:", chunk);
         setChunkCount((prev) => prev + 1);
       });
 
@@ -61,6 +71,12 @@ function App() {
         console.log("🔍 Nova análise:", data);
         setAnalysis(data);
       });
+
+      unlistenDeviceError = await audioService.onAudioDeviceError((err) => {
+        console.error("🎤 Dispositivo perdido:", err);
+        setIsRealtimeActive(false);
+        setError(`Dispositivo de áudio desconectado: ${formatError(err)}`);
+      });
     };
 
     setupListeners();
@@ -69,6 +85,7 @@ function App() {
       if (unlistenChunk) unlistenChunk();
       if (unlistenTranscription) unlistenTranscription();
       if (unlistenAnalysis) unlistenAnalysis();
+      if (unlistenDeviceError) unlistenDeviceError();
     };
   }, []);
 
@@ -88,7 +105,7 @@ function App() {
       console.log(result);
       setIsRealtimeActive(true);
     } catch (err) {
-      setError(`Erro ao iniciar real-time: ${err}`);
+      setError(`Erro ao iniciar real-time: ${formatError(err)}`);
     }
   };
 
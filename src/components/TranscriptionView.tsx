@@ -66,13 +66,13 @@ export const TranscriptionView: React.FC<Props> = ({ transcriptions }) => {
             <div
               key={item.id}
               className={`transform transition-all duration-300 animate-fadeIn ${
-                item.speaker === "user" ? "ml-12" : "mr-12"
+                item.speaker === "seller" ? "ml-12" : "mr-12"
               }`}
               style={{ animationDelay: `${index * 0.1}s` }}
             >
               <div
                 className={`group relative p-4 rounded-2xl backdrop-blur-sm border shadow-lg transition-all duration-300 hover:scale-[1.02] ${
-                  item.speaker === "user"
+                  item.speaker === "seller"
                     ? "bg-linear-to-br from-cyan-500/20 to-blue-500/20 border-cyan-500/30 hover:shadow-cyan-500/25"
                     : "bg-linear-to-br from-purple-500/20 to-pink-500/20 border-purple-500/30 hover:shadow-purple-500/25"
                 }`}
@@ -82,12 +82,12 @@ export const TranscriptionView: React.FC<Props> = ({ transcriptions }) => {
                   <div className="flex items-center gap-2">
                     <div
                       className={`w-8 h-8 rounded-full flex items-center justify-center ${
-                        item.speaker === "user"
+                        item.speaker === "seller"
                           ? "bg-cyan-500 shadow-lg shadow-cyan-500/50"
                           : "bg-purple-500 shadow-lg shadow-purple-500/50"
                       }`}
                     >
-                      {item.speaker === "user" ? (
+                      {item.speaker === "seller" ? (
                         <svg
                           className="w-5 h-5 text-white"
                           fill="currentColor"
@@ -110,7 +110,7 @@ export const TranscriptionView: React.FC<Props> = ({ transcriptions }) => {
                       )}
                     </div>
                     <span className="font-bold text-white">
-                      {item.speaker === "user" ? "You" : "Client"}
+                      {item.speaker === "seller" ? "You" : "Client"}
                     </span>
                   </div>
                   <div className="flex items-center gap-2 text-xs text-purple-300 bg-white/5 px-3 py-1 rounded-full">
@@ -137,7 +137,7 @@ export const TranscriptionView: React.FC<Props> = ({ transcriptions }) => {
                 {/* Decorative Element */}
                 <div
                   className={`absolute top-0 right-0 w-20 h-20 rounded-full opacity-10 blur-2xl ${
-                    item.speaker === "user" ? "bg-cyan-500" : "bg-purple-500"
+                    item.speaker === "seller" ? "bg-cyan-500" : "bg-purple-500"
                   }`}
                 ></div>
               </div>
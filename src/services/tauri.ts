@@ -1,36 +1,160 @@
 import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
-import { Analysis, TranscriptionResult } from "../types";
+import {
+  Analysis,
+  AudioDeviceInfo,
+  AudioError,
+  AudioLevel,
+  ChunkInfo,
+  ImportProgress,
+  LanguageChange,
+  InputSilence,
+  PartialTranscriptionEvent,
+  Session,
+  SessionRecording,
+  Speaker,
+  SpeechBoundary,
+  Transcript,
+  TranscriptionError,
+  TranscriptionEvent,
+  TranscriptionProvider,
+} from "../types";
 
 export const audioService = {
   async initializeOpenAI(apiKey: string): Promise<string> {
     return await invoke<string>("initialize_openai", { apiKey });
   },
 
-  async startRealtimeCapture(): Promise<string> {
-    return await invoke<string>("start_realtime_capture");
+  async initializeGroqWhisper(apiKey: string): Promise<string> {
+    return await invoke<string>("initialize_groq_whisper", { apiKey });
+  },
+
+  async getTranscriptionProvider(): Promise<TranscriptionProvider> {
+    return await invoke<TranscriptionProvider>("get_transcription_provider");
+  },
+
+  async setTranscriptionProvider(provider: TranscriptionProvider): Promise<string> {
+    return await invoke<string>("set_transcription_provider", { provider });
+  },
+
+  async setTranscriptionLanguage(language: string): Promise<string> {
+    return await invoke<string>("set_transcription_language", { language });
+  },
+
+  async getGlossary(): Promise<string[]> {
+    return await invoke<string[]>("get_glossary");
+  },
+
+  async setGlossary(terms: string[]): Promise<string> {
+    return await invoke<string>("set_glossary", { terms });
+  },
+
+  async setTranscriptionFallback(providers: TranscriptionProvider[]): Promise<string> {
+    return await invoke<string>("set_transcription_fallback", { providers });
+  },
+
+  async setWhisperCppModel(modelPath: string): Promise<string> {
+    return await invoke<string>("set_whisper_cpp_model", { modelPath });
+  },
+
+  async startRealtimeCapture(
+    deviceName?: string,
+    loopbackDeviceName?: string
+  ): Promise<string> {
+    return await invoke<string>("start_realtime_capture", {
+      deviceName,
+      loopbackDeviceName,
+    });
   },
 
   async stopRealtimeCapture(): Promise<string> {
     return await invoke<string>("stop_realtime_capture");
   },
 
-  async transcribe(audioPath: string): Promise<TranscriptionResult> {
-    return await invoke<TranscriptionResult>("transcribe_audio", { audioPath });
+  async pauseRealtimeCapture(): Promise<string> {
+    return await invoke<string>("pause_realtime_capture");
+  },
+
+  async resumeRealtimeCapture(): Promise<string> {
+    return await invoke<string>("resume_realtime_capture");
+  },
+
+  async getSession(): Promise<Session | null> {
+    return await invoke<Session | null>("get_session");
+  },
+
+  async getSessionRecordings(sessionId?: string): Promise<SessionRecording[]> {
+    return await invoke<SessionRecording[]>("get_session_recordings", { sessionId });
+  },
+
+  async listAudioDevices(): Promise<AudioDeviceInfo[]> {
+    return await invoke<AudioDeviceInfo[]>("list_audio_devices");
+  },
+
+  async getSelectedAudioDevice(): Promise<string | null> {
+    return await invoke<string | null>("get_selected_audio_device");
+  },
+
+  async selectAudioDevice(deviceName: string | null): Promise<string> {
+    return await invoke<string>("select_audio_device", { deviceName });
+  },
+
+  async getSelectedLoopbackDevice(): Promise<string | null> {
+    return await invoke<string | null>("get_selected_loopback_device");
+  },
+
+  async selectLoopbackDevice(deviceName: string | null): Promise<string> {
+    return await invoke<string>("select_loopback_device", { deviceName });
+  },
+
+  async transcribe(audioPath: string): Promise<Transcript> {
+    return await invoke<Transcript>("transcribe_audio", { audioPath });
+  },
+
+  async importRecording(path: string, speaker?: Speaker): Promise<string> {
+    return await invoke<string>("import_recording", { path, speaker });
+  },
+
+  async retryFailedTranscriptions(): Promise<string> {
+    return await invoke<string>("retry_failed_transcriptions");
   },
 
   async analyzeText(text: string): Promise<Analysis> {
     return await invoke<Analysis>("analyze_text", { text });
   },
 
-  onNewChunk(callback: (chunkPath: string) => void) {
-    return listen<string>("new-chunk", (event) => {
+  onNewChunk(callback: (chunk: ChunkInfo) => void) {
+    return listen<ChunkInfo>("new-chunk", (event) => {
       callback(event.payload);
     });
   },
 
-  onNewTranscription(callback: (data: any) => void) {
-    return listen("new-transcription", (event) => {
+  onNewTranscription(callback: (data: TranscriptionEvent) => void) {
+    return listen<TranscriptionEvent>("new-transcription", (event) => {
+      callback(event.payload);
+    });
+  },
+
+  onTranscriptionPartial(callback: (data: PartialTranscriptionEvent) => void) {
+    return listen<PartialTranscriptionEvent>("transcription-partial", (event) => {
+      callback(event.payload);
+    });
+  },
+
+  onTranscriptionFinal(callback: (data: TranscriptionEvent) => void) {
+    return listen<TranscriptionEvent>("transcription-final", (event) => {
+      callback(event.payload);
+    });
+  },
+
+  onTranscriptionError(callback: (error: TranscriptionError) => void) {
+    return listen<TranscriptionError>("transcription-error", (event) => {
+      callback(event.payload);
+    });
+  },
+
+  onLanguageChanged(callback: (change: LanguageChange) => void) {
+    return listen<LanguageChange>("language-changed", (event) => {
       callback(event.payload);
     });
   },
@@ -40,4 +164,52 @@ export const audioService = {
       callback(event.payload);
     });
   },
+
+  onSpeechStarted(callback: (boundary: SpeechBoundary) => void) {
+    return listen<SpeechBoundary>("speech-started", (event) => {
+      callback(event.payload);
+    });
+  },
+
+  onSpeechEnded(callback: (boundary: SpeechBoundary) => void) {
+    return listen<SpeechBoundary>("speech-ended", (event) => {
+      callback(event.payload);
+    });
+  },
+
+  onAudioLevel(callback: (level: AudioLevel) => void) {
+    return listen<AudioLevel>("audio-level", (event) => {
+      callback(event.payload);
+    });
+  },
+
+  onClippingDetected(callback: (level: AudioLevel) => void) {
+    return listen<AudioLevel>("clipping-detected", (event) => {
+      callback(event.payload);
+    });
+  },
+
+  onInputSilent(callback: (silence: InputSilence) => void) {
+    return listen<InputSilence>("input-silent", (event) => {
+      callback(event.payload);
+    });
+  },
+
+  onImportProgress(callback: (progress: ImportProgress) => void) {
+    return listen<ImportProgress>("import-progress", (event) => {
+      callback(event.payload);
+    });
+  },
+
+  onImportFinished(callback: (session: Session) => void) {
+    return listen<Session>("import-finished", (event) => {
+      callback(event.payload);
+    });
+  },
+
+  onAudioDeviceError(callback: (error: AudioError) => void) {
+    return listen<AudioError>("audio-device-error", (event) => {
+      callback(event.payload);
+    });
+  },
 };
@@ -1,8 +1,12 @@
+export type Speaker = "seller" | "customer";
+
+export type TranscriptionProvider = "groq" | "openai" | "local" | "whispercpp" | "mock";
+
 export interface Transcription {
   id: string;
   text: string;
   timestamp: Date;
-  speaker: "user" | "other";
+  speaker: Speaker;
 }
 
 export interface Analysis {
@@ -12,17 +16,139 @@ export interface Analysis {
   suggestions: string[];
 }
 
-export interface TranscriptionResult {
-  success: boolean;
-  full_text?: string;
-  segments?: TranscriptionSegment[];
-  error?: string;
-  language?: string;
-  duration?: number;
+export interface TranscriptWord {
+  word: string;
+  start_ms: number;
+  end_ms: number;
+  confidence: number | null;
+}
+
+export interface TranscriptSegment {
+  start_ms: number;
+  end_ms: number;
+  text: string;
+  confidence: number | null;
+  no_speech_prob: number | null;
+  words: TranscriptWord[];
+  language: string | null;
 }
 
-export interface TranscriptionSegment {
-  start: number;
-  end: number;
+// Formato único devolvido por todos os backends de transcrição
+export interface Transcript {
   text: string;
+  language: string | null;
+  duration_ms: number | null;
+  segments: TranscriptSegment[];
+  confidence: number | null;
+  provider: TranscriptionProvider;
+  latency_ms: number;
+}
+
+export interface TranscriptionEvent extends Omit<Transcript, "provider"> {
+  // Ausente no final vazio de um chunk descartado (falha, silêncio ou filtro)
+  provider?: TranscriptionProvider;
+  timestamp: number;
+  speaker: Speaker;
+  // Posição do chunk na sessão, em ms
+  start_ms: number;
+  end_ms: number;
+  // Mesmo id dos parciais que esta transcrição substitui
+  utterance_id: string;
+}
+
+// Texto provisório de uma fala em andamento; substituído pelo próximo parcial
+// ou pelo "transcription-final" com o mesmo utterance_id (texto vazio: descartar)
+export interface PartialTranscriptionEvent extends Transcript {
+  utterance_id: string;
+  speaker: Speaker;
+}
+
+export interface AudioDeviceInfo {
+  name: string;
+  is_default: boolean;
+  is_loopback: boolean;
+}
+
+export interface AudioError {
+  kind:
+    | "no_default_device"
+    | "device_not_found"
+    | "device_disconnected"
+    | "unsupported_format"
+    | "stream"
+    | "busy";
+  message?: string;
+}
+
+export interface ChunkInfo {
+  speaker: Speaker;
+  start_ms: number;
+  end_ms: number;
+  path?: string;
+}
+
+export interface TranscriptionError {
+  /** `path` aponta para o WAV guardado até a transcrição dar certo */
+  chunk: ChunkInfo;
+  provider?: string;
+  error: string;
+  attempts: number;
+}
+
+export interface LanguageChange {
+  speaker: Speaker;
+  from: string;
+  to: string;
+  at_ms: number;
+}
+
+export interface SpeechBoundary {
+  speaker: Speaker;
+  speaking: boolean;
+  at_ms: number;
+}
+
+export interface AudioLevel {
+  speaker: Speaker;
+  rms: number;
+  peak: number;
+  at_ms: number;
+}
+
+export interface InputSilence {
+  speaker: Speaker;
+  seconds: number;
+  at_ms: number;
+}
+
+export interface Session {
+  id: string;
+  started_at: number;
+  paused: boolean;
+  // Código ISO 639-1 ou "auto"
+  language: string;
+  detected_languages: Partial<Record<Speaker, string>>;
+  transcript: TranscriptionEvent[];
+  timeline: ChunkInfo[];
+  analysis: Analysis;
+  filter_stats: FilterStats;
+}
+
+// Alucinações descartadas pelo filtro na sessão
+export interface FilterStats {
+  low_energy_chunks: number;
+  no_speech_segments: number;
+  blocklisted_segments: number;
+}
+
+export interface SessionRecording {
+  speaker: Speaker;
+  path: string;
+}
+
+export interface ImportProgress {
+  processed_chunks: number;
+  // Duração do arquivo, quando o formato informa
+  duration_ms: number | null;
+  position_ms: number;
 }